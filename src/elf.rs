@@ -1,111 +1,460 @@
 mod common;
+pub mod compression;
+pub mod core_dump;
+pub mod diff;
+pub mod dump;
+pub mod dynamic;
+pub mod edit;
 pub mod elf_header;
+pub mod elf_machine;
 pub mod elf_symbol;
+pub mod error;
+pub mod group;
+pub mod hash;
+pub mod header_flags;
+pub mod mapped;
+pub mod note;
 pub mod program_header;
+pub mod reader;
+pub mod relocation;
+mod relocation_types;
 pub mod section_header;
+pub mod security;
+pub mod table;
+pub mod version;
+pub mod writer;
 
 use crate::elf::{
-    elf_header::ElfHeader,
-    elf_symbol::{ElfSymbol, ElfSymbolTable},
-    program_header::ElfProgramHeader,
-    section_header::ElfSectionHeader,
+    dynamic::{ElfDynamic, ElfDynamicTable},
+    elf_header::{ElfHeader, EI_CLASS, ELF_CLASS_32},
+    elf_symbol::{
+        parse_symtab_shndx, ElfSymbol, ElfSymbolTable, ELF_SYMTAB_SHNDX_SIZE, SHN_XINDEX,
+    },
+    error::{ElfError, ElfTable},
+    program_header::{ElfProgramHeader, PN_XNUM, PT_DYNAMIC},
+    reader::{ElfReader, Endian},
+    relocation::{ElfRel, ElfRela, ElfRelocationTable, ElfRelocations},
+    section_header::{ElfSectionHeader, SHT_DYNAMIC, SHT_NOBITS, SHT_REL, SHT_RELA},
+    table::LazyTable,
 };
-use std::fs;
+
+pub use crate::elf::mapped::map_file;
 
 type ElfHalf = u16;
 type ElfWord = u32;
 type ElfXword = u64;
+type ElfSxword = i64;
 type ElfAddr = u64;
 type ElfOff = u64;
 type ElfSection = u16;
-type ElfIdent = u128;
+type ElfIdent = [u8; elf_header::EI_NIDENT];
 
+/// An ELF file borrowed from a byte slice, typically one returned by
+/// [`map_file`]. Only the ELF header is decoded up front; the tables are
+/// decoded entry by entry as they are accessed.
 #[derive(Debug)]
-pub struct ElfFile {
-    pub data: Vec<u8>,
+pub struct ElfFile<'data> {
+    pub data: &'data [u8],
     pub header: ElfHeader,
-    pub section_headers: Vec<ElfSectionHeader>,
-    pub program_headers: Vec<ElfProgramHeader>,
-    pub symbol_tables: Vec<ElfSymbolTable>,
+    pub section_headers: LazyTable<'data, ElfSectionHeader>,
+    pub program_headers: LazyTable<'data, ElfProgramHeader>,
+    /// The section header string table index, taken from section 0 when
+    /// `e_shstrndx` is `SHN_XINDEX`.
+    pub string_table_index: usize,
+    pub symbol_tables: Vec<ElfSymbolTable<'data>>,
+    pub relocation_tables: Vec<ElfRelocationTable<'data>>,
+    pub dynamic_table: Option<ElfDynamicTable<'data>>,
+    /// Errors that kept a symbol, relocation or dynamic table out of the
+    /// tables above. They are reported when that kind of table is shown.
+    pub table_errors: Vec<ElfError>,
 }
 
-impl ElfFile {
-    pub fn read_from_file(filename: &String) -> Self {
-        let data = fs::read(filename).unwrap();
+impl<'data> ElfFile<'data> {
+    pub fn parse(data: &'data [u8]) -> Result<Self, ElfError> {
+        let reader = ElfReader::new(data);
 
-        let header = Self::read_header(&data);
-        let section_headers = Self::read_section_headers(&header, &data);
-        let program_headers = Self::read_program_headers(&header, &data);
-        let symbols = Self::read_symbols(&section_headers, &data);
+        let header = ElfHeader::parse(&reader)?;
+        let first_section = Self::read_first_section_header(reader, &header)?;
+        let section_headers = Self::read_section_headers(reader, &header, first_section)?;
+        let string_table_index =
+            Self::read_string_table_index(&header, first_section, &section_headers)?;
+        let program_headers = Self::read_program_headers(reader, &header, first_section)?;
+        // A broken table only affects the commands that show it.
+        let mut table_errors = Vec::new();
+        let symbols = Self::read_symbols(reader, &section_headers, &mut table_errors);
+        let relocations = Self::read_relocations(reader, &section_headers, &mut table_errors);
+        let dynamic = Self::read_dynamic(reader, &section_headers, &program_headers)
+            .unwrap_or_else(|e| {
+                table_errors.push(e);
+                None
+            });
 
-        Self {
+        Ok(Self {
             data,
             header,
             section_headers,
             program_headers,
+            string_table_index,
             symbol_tables: symbols,
+            relocation_tables: relocations,
+            dynamic_table: dynamic,
+            table_errors,
+        })
+    }
+
+    /// Prints the errors that kept tables of kind `table` from being read.
+    pub(crate) fn warn_table_errors(&self, table: ElfTable) {
+        for e in &self.table_errors {
+            if e.table() == Some(table) {
+                eprintln!("readelf: Warning: {}", e);
+            }
+        }
+    }
+
+    /// Cheaply checks whether `data` starts with a valid ELF header, without
+    /// reading any of the tables it points to.
+    pub fn is_elf(data: &[u8]) -> bool {
+        ElfHeader::parse(&ElfReader::new(data)).is_ok()
+    }
+
+    pub fn endian(&self) -> Endian {
+        Endian::from_ident(&self.header.ident)
+    }
+
+    pub fn is_32bit(&self) -> bool {
+        self.header.ident[EI_CLASS] == ELF_CLASS_32
+    }
+
+    pub(crate) fn reader(&self) -> ElfReader<'data> {
+        ElfReader::new(self.data)
+    }
+
+    /// Reads section 0 when the ELF header defers to it: `e_shnum` is 0 with a
+    /// section header table present, `e_shstrndx` is `SHN_XINDEX`, or
+    /// `e_phnum` is `PN_XNUM`.
+    fn read_first_section_header(
+        reader: ElfReader<'data>,
+        header: &ElfHeader,
+    ) -> Result<Option<ElfSectionHeader>, ElfError> {
+        let escaped = header.section_header_num == 0
+            || header.string_table_index == SHN_XINDEX
+            || header.program_header_num == PN_XNUM;
+        if !escaped || header.section_header_offset == 0 {
+            return Ok(None);
         }
+        Self::check_table(
+            &reader,
+            ElfTable::SectionHeaders,
+            header.section_header_offset,
+            ElfSectionHeader::entry_size(&reader) as u64,
+            1,
+        )?;
+        Ok(ElfSectionHeader::parse(
+            &reader,
+            header.section_header_offset as usize,
+        ))
     }
 
-    fn read_header(data: &Vec<u8>) -> ElfHeader {
-        let (_, body, _) = unsafe { data.align_to::<ElfHeader>() };
-        *&body[0]
+    fn read_section_headers(
+        reader: ElfReader<'data>,
+        header: &ElfHeader,
+        first_section: Option<ElfSectionHeader>,
+    ) -> Result<LazyTable<'data, ElfSectionHeader>, ElfError> {
+        let entry_size = header.section_header_size as u64;
+        let num = match (header.section_header_num, first_section) {
+            (0, Some(sh)) => sh.size,
+            (num, _) => num as u64,
+        };
+        if num == 0 {
+            return Ok(LazyTable::empty(reader, ElfSectionHeader::parse));
+        }
+        // ElfHeader::parse only checks e_shentsize when e_shnum is set.
+        if header.section_header_num == 0
+            && entry_size != ElfSectionHeader::entry_size(&reader) as u64
+        {
+            return Err(ElfError::BadHeaderSize {
+                field: "e_shentsize",
+                size: header.section_header_size,
+                expected: ElfSectionHeader::entry_size(&reader) as u16,
+            });
+        }
+        Self::check_table(
+            &reader,
+            ElfTable::SectionHeaders,
+            header.section_header_offset,
+            entry_size,
+            num,
+        )?;
+
+        Ok(LazyTable::new(
+            reader,
+            header.section_header_offset as usize,
+            entry_size as usize,
+            num as usize,
+            ElfSectionHeader::parse,
+        ))
     }
 
-    fn read_section_headers(header: &ElfHeader, data: &Vec<u8>) -> Vec<ElfSectionHeader> {
-        let mut section_headers: Vec<ElfSectionHeader> = Vec::new();
-        for i in 0..header.section_header_num {
-            let start_addr = header.section_header_offset as usize
-                + header.section_header_size as usize * i as usize;
-            let end_addr = start_addr as usize + header.section_header_size as usize;
-            let (_, body, _) = unsafe { data[start_addr..end_addr].align_to::<ElfSectionHeader>() };
-            let section_header = &body[0];
-            section_headers.push(*section_header);
+    fn read_string_table_index(
+        header: &ElfHeader,
+        first_section: Option<ElfSectionHeader>,
+        section_headers: &LazyTable<'data, ElfSectionHeader>,
+    ) -> Result<usize, ElfError> {
+        let index = match (header.string_table_index, first_section) {
+            (SHN_XINDEX, Some(sh)) => sh.link as usize,
+            (index, _) => index as usize,
+        };
+        if !section_headers.is_empty() && index >= section_headers.len() {
+            return Err(ElfError::BadStringTableIndex(index));
         }
-        section_headers
-    }
-
-    fn read_program_headers(header: &ElfHeader, data: &Vec<u8>) -> Vec<ElfProgramHeader> {
-        let mut program_headers: Vec<ElfProgramHeader> = Vec::new();
-        for i in 0..header.program_header_num {
-            let start_addr = header.program_header_offset as usize
-                + header.program_header_size as usize * i as usize;
-            let end_addr = start_addr as usize + header.program_header_size as usize;
-            let (_, body, _) = unsafe { data[start_addr..end_addr].align_to::<ElfProgramHeader>() };
-            let program_header = &body[0];
-            program_headers.push(*program_header);
+        Ok(index)
+    }
+
+    fn read_program_headers(
+        reader: ElfReader<'data>,
+        header: &ElfHeader,
+        first_section: Option<ElfSectionHeader>,
+    ) -> Result<LazyTable<'data, ElfProgramHeader>, ElfError> {
+        let entry_size = header.program_header_size as u64;
+        let num = match (header.program_header_num, first_section) {
+            (PN_XNUM, Some(sh)) => sh.info as u64,
+            (num, _) => num as u64,
+        };
+        if num == 0 {
+            return Ok(LazyTable::empty(reader, ElfProgramHeader::parse));
         }
-        program_headers
+        Self::check_table(
+            &reader,
+            ElfTable::ProgramHeaders,
+            header.program_header_offset,
+            entry_size,
+            num,
+        )?;
+
+        Ok(LazyTable::new(
+            reader,
+            header.program_header_offset as usize,
+            entry_size as usize,
+            num as usize,
+            ElfProgramHeader::parse,
+        ))
     }
 
     fn read_symbols(
-        section_headers: &Vec<ElfSectionHeader>,
-        data: &Vec<u8>,
-    ) -> Vec<ElfSymbolTable> {
-        section_headers
+        reader: ElfReader<'data>,
+        section_headers: &LazyTable<'data, ElfSectionHeader>,
+        errors: &mut Vec<ElfError>,
+    ) -> Vec<ElfSymbolTable<'data>> {
+        let mut tables = Vec::new();
+        for (i, sh) in section_headers.iter().enumerate() {
+            if !matches!(
+                sh.section_type,
+                section_header::SHT_SYMTAB | section_header::SHT_DYNSYM
+            ) {
+                continue;
+            }
+            let symbols = match Self::read_section_table(
+                reader,
+                ElfTable::Symbols,
+                i,
+                &sh,
+                ElfSymbol::entry_size(&reader),
+                ElfSymbol::parse,
+            ) {
+                Ok(symbols) => symbols,
+                Err(e) => {
+                    errors.push(e);
+                    continue;
+                }
+            };
+            let extended_section_indexes =
+                Self::read_symbol_section_indexes(reader, section_headers, i).unwrap_or_else(|e| {
+                    errors.push(e);
+                    None
+                });
+            tables.push(ElfSymbolTable {
+                index: i,
+                symbols,
+                extended_section_indexes,
+            });
+        }
+        tables
+    }
+
+    /// Reads the `SHT_SYMTAB_SHNDX` section attached to the symbol table in
+    /// section `symtab_index`, if there is one.
+    fn read_symbol_section_indexes(
+        reader: ElfReader<'data>,
+        section_headers: &LazyTable<'data, ElfSectionHeader>,
+        symtab_index: usize,
+    ) -> Result<Option<LazyTable<'data, ElfWord>>, ElfError> {
+        let found = section_headers.iter().enumerate().find(|(_, sh)| {
+            sh.section_type == section_header::SHT_SYMTAB_SHNDX && sh.link as usize == symtab_index
+        });
+        match found {
+            Some((i, sh)) => Ok(Some(Self::read_section_table(
+                reader,
+                ElfTable::Symbols,
+                i,
+                &sh,
+                ELF_SYMTAB_SHNDX_SIZE,
+                parse_symtab_shndx,
+            )?)),
+            None => Ok(None),
+        }
+    }
+
+    fn read_relocations(
+        reader: ElfReader<'data>,
+        section_headers: &LazyTable<'data, ElfSectionHeader>,
+        errors: &mut Vec<ElfError>,
+    ) -> Vec<ElfRelocationTable<'data>> {
+        let mut tables = Vec::new();
+        for (i, sh) in section_headers.iter().enumerate() {
+            let relocations = match sh.section_type {
+                SHT_RELA => Self::read_section_table(
+                    reader,
+                    ElfTable::Relocations,
+                    i,
+                    &sh,
+                    ElfRela::entry_size(&reader),
+                    ElfRela::parse,
+                )
+                .map(ElfRelocations::Rela),
+                SHT_REL => Self::read_section_table(
+                    reader,
+                    ElfTable::Relocations,
+                    i,
+                    &sh,
+                    ElfRel::entry_size(&reader),
+                    ElfRel::parse,
+                )
+                .map(ElfRelocations::Rel),
+                _ => continue,
+            };
+            match relocations {
+                Ok(relocations) => tables.push(ElfRelocationTable {
+                    index: i,
+                    relocations,
+                }),
+                Err(e) => errors.push(e),
+            }
+        }
+        tables
+    }
+
+    fn read_dynamic(
+        reader: ElfReader<'data>,
+        section_headers: &LazyTable<'data, ElfSectionHeader>,
+        program_headers: &LazyTable<'data, ElfProgramHeader>,
+    ) -> Result<Option<ElfDynamicTable<'data>>, ElfError> {
+        let section = section_headers
             .iter()
             .enumerate()
-            .filter(|(_, sh)| match sh.section_type {
-                section_header::SHT_SYMTAB | section_header::SHT_DYNSYM => true,
-                _ => false,
-            })
-            .map(|(i, sh)| ElfSymbolTable {
+            .find(|(_, sh)| sh.section_type == SHT_DYNAMIC);
+        if let Some((i, sh)) = section {
+            return Ok(Some(ElfDynamicTable {
+                index: Some(i),
+                offset: sh.offset,
+                entries: Self::read_section_table(
+                    reader,
+                    ElfTable::Dynamic,
+                    i,
+                    &sh,
+                    ElfDynamic::entry_size(&reader),
+                    ElfDynamic::parse,
+                )?,
+            }));
+        }
+
+        let segment = program_headers
+            .iter()
+            .enumerate()
+            .find(|(_, ph)| ph.segment_type == PT_DYNAMIC);
+        let (i, ph) = match segment {
+            Some(segment) => segment,
+            None => return Ok(None),
+        };
+        if !reader.contains_table(ph.offset, ph.file_size, 1) {
+            return Err(ElfError::OutOfRange {
+                table: ElfTable::Dynamic,
                 index: i,
-                symbols: Self::read_symbols_from_section(sh, data),
-            })
-            .collect()
-    }
-
-    fn read_symbols_from_section(sh: &ElfSectionHeader, data: &Vec<u8>) -> Vec<ElfSymbol> {
-        let mut symbols: Vec<ElfSymbol> = Vec::new();
-        let symbol_num = sh.size / sh.entry_size;
-        for i in 0..symbol_num {
-            let start_addr = sh.offset as usize + sh.entry_size as usize * i as usize;
-            let end_addr = start_addr as usize + sh.entry_size as usize;
-            let (_, body, _) = unsafe { data[start_addr..end_addr].align_to::<ElfSymbol>() };
-            let symbol = &body[0];
-            symbols.push(*symbol);
+                offset: ph.offset,
+                size: ph.file_size,
+            });
+        }
+        let entry_size = ElfDynamic::entry_size(&reader);
+        Ok(Some(ElfDynamicTable {
+            index: None,
+            offset: ph.offset,
+            entries: LazyTable::new(
+                reader,
+                ph.offset as usize,
+                entry_size,
+                ph.file_size as usize / entry_size,
+                ElfDynamic::parse,
+            ),
+        }))
+    }
+
+    /// Builds a lazy view over the fixed-size entries of section `index`,
+    /// checking its entry size and extent.
+    fn read_section_table<T>(
+        reader: ElfReader<'data>,
+        table: ElfTable,
+        index: usize,
+        sh: &ElfSectionHeader,
+        min_entry_size: usize,
+        parse: fn(&ElfReader, usize) -> Option<T>,
+    ) -> Result<LazyTable<'data, T>, ElfError> {
+        if sh.section_type == SHT_NOBITS || sh.size == 0 {
+            return Ok(LazyTable::empty(reader, parse));
+        }
+        if sh.entry_size < min_entry_size as u64 {
+            return Err(ElfError::BadEntrySize {
+                table,
+                index,
+                size: sh.entry_size,
+            });
+        }
+        if !reader.contains_table(sh.offset, sh.size, 1) {
+            return Err(ElfError::OutOfRange {
+                table,
+                index,
+                offset: sh.offset,
+                size: sh.size,
+            });
+        }
+
+        Ok(LazyTable::new(
+            reader,
+            sh.offset as usize,
+            sh.entry_size as usize,
+            (sh.size / sh.entry_size) as usize,
+            parse,
+        ))
+    }
+
+    /// Checks that a table of `num` entries fits in the file, reporting the
+    /// first entry that does not.
+    fn check_table(
+        reader: &ElfReader,
+        table: ElfTable,
+        offset: u64,
+        entry_size: u64,
+        num: u64,
+    ) -> Result<(), ElfError> {
+        if reader.contains_table(offset, entry_size, num) {
+            return Ok(());
         }
-        symbols
+        let index = (0..num)
+            .find(|&i| !reader.contains_table(offset.saturating_add(entry_size * i), entry_size, 1))
+            .unwrap_or(0);
+        Err(ElfError::OutOfRange {
+            table,
+            index: index as usize,
+            offset: offset.saturating_add(entry_size * index),
+            size: entry_size,
+        })
     }
 }
@@ -11,13 +11,54 @@ pub fn get_flag_char<T: Into<u64>>(flags: T, value: T, sign: char) -> char {
     }
 }
 
-impl ElfFile {
+/// Names the bits of `flags` listed in `names`, in table order, and appends
+/// any bits no entry covers as `<unknown: ...>`. The parts are joined with
+/// `separator`; no bits set gives an empty string.
+pub fn get_flag_names<T: Into<u64> + Copy>(
+    flags: T,
+    names: &[(T, &str)],
+    separator: &str,
+) -> String {
+    let flags = flags.into();
+    let mut parts: Vec<String> = names
+        .iter()
+        .filter(|&&(flag, _)| flags & flag.into() != 0)
+        .map(|(_, name)| name.to_string())
+        .collect();
+    let known = names.iter().fold(0, |acc, &(flag, _)| acc | flag.into());
+    if flags & !known != 0 {
+        parts.push(format!("<unknown: {:x}>", flags & !known));
+    }
+    parts.join(separator)
+}
+
+impl ElfFile<'_> {
     pub fn get_name_from_strtab(&self, sh: &ElfSectionHeader, index: usize) -> String {
-        let start_addr = sh.offset as usize + index;
-        self.data[start_addr..]
+        self.get_string(sh.offset as usize, sh.size as usize, index)
+    }
+
+    /// Reads the NUL-terminated string at `index` in the string table that
+    /// occupies `size` bytes at file offset `offset`.
+    pub fn get_string(&self, offset: usize, size: usize, index: usize) -> String {
+        let end_addr = offset.saturating_add(size);
+        self.data
+            .get(offset..end_addr.min(self.data.len()))
+            .and_then(|strtab| strtab.get(index..))
+            .unwrap_or_default()
             .iter()
             .take_while(|&&v| v != 0)
             .map(|&v| v as char)
             .collect()
     }
+
+    pub fn get_linked_section(&self, sh: &ElfSectionHeader) -> Option<ElfSectionHeader> {
+        self.section_headers.get(sh.link as usize)
+    }
+
+    pub fn get_section_name(&self, sh: &ElfSectionHeader) -> String {
+        match self.section_headers.get(self.string_table_index) {
+            Some(shstrtab) => self.get_name_from_strtab(&shstrtab, sh.name as usize),
+            None => String::new(),
+        }
+    }
 }
@@ -0,0 +1,169 @@
+use crate::elf::reader::ElfReader;
+use crate::elf::section_header::SHF_COMPRESSED;
+use crate::elf::*;
+
+use flate2::read::ZlibDecoder;
+use ruzstd::decoding::StreamingDecoder;
+use std::borrow::Cow;
+use std::convert::{TryFrom, TryInto};
+use std::io::Read;
+
+/// The header at the start of a `SHF_COMPRESSED` section.
+#[derive(Copy, Clone, Debug)]
+pub struct ElfCompressionHeader {
+    pub compression_type: ElfWord,
+    pub size: ElfXword,
+    pub alignment: ElfXword,
+}
+
+pub const ELF32_COMPRESSION_HEADER_SIZE: usize = 12;
+pub const ELF64_COMPRESSION_HEADER_SIZE: usize = 24;
+
+pub const ELFCOMPRESS_ZLIB: u32 = 1;
+pub const ELFCOMPRESS_ZSTD: u32 = 2;
+
+/// Legacy GNU `.zdebug_*` sections start with this magic followed by the
+/// uncompressed size as a 64-bit big-endian integer.
+pub const ZDEBUG_MAGIC: &[u8] = b"ZLIB";
+pub const ZDEBUG_HEADER_SIZE: usize = 12;
+
+impl ElfCompressionHeader {
+    pub(crate) fn entry_size(reader: &ElfReader) -> usize {
+        if reader.is_32bit() {
+            ELF32_COMPRESSION_HEADER_SIZE
+        } else {
+            ELF64_COMPRESSION_HEADER_SIZE
+        }
+    }
+
+    pub(crate) fn parse(reader: &ElfReader, data: &[u8]) -> Option<Self> {
+        let mut c = reader.cursor_over(data.get(..Self::entry_size(reader))?);
+        let compression_type = c.u32()?;
+        if !reader.is_32bit() {
+            c.skip(4)?;
+        }
+        Some(Self {
+            compression_type,
+            size: c.addr()?,
+            alignment: c.addr()?,
+        })
+    }
+}
+
+impl<'data> ElfFile<'data> {
+    /// Returns the compression header of a `SHF_COMPRESSED` section.
+    pub fn compression_header(&self, sh: &ElfSectionHeader) -> Option<ElfCompressionHeader> {
+        if sh.flags & SHF_COMPRESSED == 0 {
+            return None;
+        }
+        ElfCompressionHeader::parse(&self.reader(), self.section_data(sh)?)
+    }
+
+    /// Returns whether a section is a legacy GNU `.zdebug_*` section.
+    pub fn is_zdebug_section(&self, sh: &ElfSectionHeader) -> bool {
+        self.get_section_name(sh).starts_with(".zdebug")
+            && self
+                .section_data(sh)
+                .is_some_and(|data| data.starts_with(ZDEBUG_MAGIC))
+    }
+
+    /// Returns the contents of section `index`, decompressing `SHF_COMPRESSED`
+    /// and `.zdebug_*` sections. Other sections are borrowed from the file.
+    pub fn decompressed_section_data(&self, index: usize) -> Result<Cow<'data, [u8]>, ElfError> {
+        let sh = self
+            .section_headers
+            .get(index)
+            .ok_or(ElfError::NoSuchSection(index))?;
+        let data = self.section_data(&sh).ok_or(ElfError::OutOfRange {
+            table: ElfTable::SectionData,
+            index,
+            offset: sh.offset,
+            size: sh.size,
+        })?;
+
+        if let Some(chdr) = self.compression_header(&sh) {
+            let compressed = &data[ElfCompressionHeader::entry_size(&self.reader())..];
+            let decompressed = match chdr.compression_type {
+                ELFCOMPRESS_ZLIB => inflate(compressed, chdr.size),
+                ELFCOMPRESS_ZSTD => decode_zstd(compressed, chdr.size),
+                compression_type => {
+                    return Err(ElfError::UnsupportedCompression {
+                        index,
+                        compression_type,
+                    })
+                }
+            };
+            return decompressed
+                .map(Cow::Owned)
+                .map_err(|message| ElfError::Decompress { index, message });
+        }
+
+        if self.is_zdebug_section(&sh) {
+            let size = data
+                .get(ZDEBUG_MAGIC.len()..ZDEBUG_HEADER_SIZE)
+                .and_then(|size| size.try_into().ok())
+                .map(u64::from_be_bytes);
+            return match size {
+                Some(size) => inflate(&data[ZDEBUG_HEADER_SIZE..], size),
+                None => Err("truncated .zdebug header".to_string()),
+            }
+            .map(Cow::Owned)
+            .map_err(|message| ElfError::Decompress { index, message });
+        }
+
+        Ok(Cow::Borrowed(data))
+    }
+}
+
+/// The most that is reserved up front for each compressed byte; zlib cannot
+/// expand data further. The buffer grows past that if it has to.
+const MAX_RESERVED_RATIO: u64 = 1032;
+
+/// Reserves room for the `size` bytes a header claims, without trusting it
+/// beyond what `compressed_size` bytes could plausibly expand to. The check
+/// of the final size is what rejects a wrong claim.
+fn allocate(size: u64, compressed_size: usize) -> Result<Vec<u8>, String> {
+    let reserved = size.min((compressed_size as u64).saturating_mul(MAX_RESERVED_RATIO));
+    let reserved =
+        usize::try_from(reserved).map_err(|_| format!("size 0x{:x} is too large", size))?;
+    let mut buffer = Vec::new();
+    buffer
+        .try_reserve_exact(reserved)
+        .map_err(|_| format!("size 0x{:x} is too large", size))?;
+    Ok(buffer)
+}
+
+fn inflate(data: &[u8], size: u64) -> Result<Vec<u8>, String> {
+    let mut buffer = allocate(size, data.len())?;
+    ZlibDecoder::new(data)
+        .take(size.saturating_add(1))
+        .read_to_end(&mut buffer)
+        .map_err(|e| e.to_string())?;
+    check_size(buffer, size)
+}
+
+fn decode_zstd(data: &[u8], size: u64) -> Result<Vec<u8>, String> {
+    let mut buffer = allocate(size, data.len())?;
+    // The section may hold several frames back to back.
+    let mut input = data;
+    while !input.is_empty() && buffer.len() as u64 <= size {
+        let limit = size.saturating_add(1) - buffer.len() as u64;
+        StreamingDecoder::new(&mut input)
+            .map_err(|e| e.to_string())?
+            .take(limit)
+            .read_to_end(&mut buffer)
+            .map_err(|e| e.to_string())?;
+    }
+    check_size(buffer, size)
+}
+
+fn check_size(buffer: Vec<u8>, size: u64) -> Result<Vec<u8>, String> {
+    if buffer.len() as u64 != size {
+        return Err(format!(
+            "decompressed to 0x{:x} bytes, expected 0x{:x}",
+            buffer.len(),
+            size
+        ));
+    }
+    Ok(buffer)
+}
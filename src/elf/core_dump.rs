@@ -0,0 +1,531 @@
+use crate::elf::elf_header::ET_CORE;
+use crate::elf::elf_machine::ElfMachine;
+use crate::elf::note::{
+    ElfNote, ELF_NOTE_CORE, NT_AUXV, NT_FILE, NT_PRPSINFO, NT_PRSTATUS, NT_SIGINFO,
+};
+use crate::elf::reader::ElfCursor;
+use crate::elf::*;
+
+use prettytable::{format, row, Table};
+
+/// A thread of a core dump, decoded from an `NT_PRSTATUS` note.
+#[derive(Clone, Debug)]
+pub struct ElfPrStatus {
+    pub signo: i32,
+    pub code: i32,
+    pub errno: i32,
+    /// The signal the thread was stopped by.
+    pub cursig: u16,
+    pub sigpend: u64,
+    pub sighold: u64,
+    pub pid: i32,
+    pub ppid: i32,
+    pub pgrp: i32,
+    pub sid: i32,
+    /// The general purpose registers in the kernel's `user_regs_struct`
+    /// order; see [`core_register_names`].
+    pub registers: Vec<u64>,
+}
+
+/// The process of a core dump, decoded from an `NT_PRPSINFO` note.
+#[derive(Clone, Debug)]
+pub struct ElfPrPsInfo {
+    pub state: u8,
+    pub sname: u8,
+    pub zombie: u8,
+    pub nice: i8,
+    pub flags: u64,
+    pub uid: u32,
+    pub gid: u32,
+    pub pid: i32,
+    pub ppid: i32,
+    pub pgrp: i32,
+    pub sid: i32,
+    pub fname: String,
+    pub psargs: String,
+}
+
+/// An entry of the `NT_FILE` note: a file mapped at `start..end`.
+#[derive(Clone, Debug)]
+pub struct ElfMappedFile {
+    pub start: u64,
+    pub end: u64,
+    /// The file offset of the mapping, in bytes.
+    pub offset: u64,
+    pub path: String,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ElfAuxvEntry {
+    pub key: u64,
+    pub value: u64,
+}
+
+/// The `siginfo_t` of the signal that killed the process, decoded from an
+/// `NT_SIGINFO` note.
+#[derive(Copy, Clone, Debug)]
+pub struct ElfSigInfo {
+    pub signo: i32,
+    pub errno: i32,
+    pub code: i32,
+    /// The faulting address for `SIGSEGV`, `SIGBUS`, `SIGILL` and `SIGFPE`.
+    pub addr: u64,
+    /// The sender of signals raised with `kill` and friends.
+    pub pid: i32,
+    pub uid: u32,
+}
+
+pub const AT_NULL: u64 = 0;
+pub const AT_IGNORE: u64 = 1;
+pub const AT_EXECFD: u64 = 2;
+pub const AT_PHDR: u64 = 3;
+pub const AT_PHENT: u64 = 4;
+pub const AT_PHNUM: u64 = 5;
+pub const AT_PAGESZ: u64 = 6;
+pub const AT_BASE: u64 = 7;
+pub const AT_FLAGS: u64 = 8;
+pub const AT_ENTRY: u64 = 9;
+pub const AT_NOTELF: u64 = 10;
+pub const AT_UID: u64 = 11;
+pub const AT_EUID: u64 = 12;
+pub const AT_GID: u64 = 13;
+pub const AT_EGID: u64 = 14;
+pub const AT_PLATFORM: u64 = 15;
+pub const AT_HWCAP: u64 = 16;
+pub const AT_CLKTCK: u64 = 17;
+pub const AT_SECURE: u64 = 23;
+pub const AT_BASE_PLATFORM: u64 = 24;
+pub const AT_RANDOM: u64 = 25;
+pub const AT_HWCAP2: u64 = 26;
+pub const AT_RSEQ_FEATURE_SIZE: u64 = 27;
+pub const AT_RSEQ_ALIGN: u64 = 28;
+pub const AT_HWCAP3: u64 = 29;
+pub const AT_HWCAP4: u64 = 30;
+pub const AT_EXECFN: u64 = 31;
+pub const AT_SYSINFO: u64 = 32;
+pub const AT_SYSINFO_EHDR: u64 = 33;
+pub const AT_MINSIGSTKSZ: u64 = 51;
+
+pub const SIGILL: i32 = 4;
+pub const SIGBUS: i32 = 7;
+pub const SIGFPE: i32 = 8;
+pub const SIGSEGV: i32 = 11;
+
+pub const SI_USER: i32 = 0;
+pub const SI_KERNEL: i32 = 0x80;
+pub const SI_QUEUE: i32 = -1;
+pub const SI_TKILL: i32 = -6;
+
+const SIGNAL_NAMES: &[&str] = &[
+    "SIGHUP",
+    "SIGINT",
+    "SIGQUIT",
+    "SIGILL",
+    "SIGTRAP",
+    "SIGABRT",
+    "SIGBUS",
+    "SIGFPE",
+    "SIGKILL",
+    "SIGUSR1",
+    "SIGSEGV",
+    "SIGUSR2",
+    "SIGPIPE",
+    "SIGALRM",
+    "SIGTERM",
+    "SIGSTKFLT",
+    "SIGCHLD",
+    "SIGCONT",
+    "SIGSTOP",
+    "SIGTSTP",
+    "SIGTTIN",
+    "SIGTTOU",
+    "SIGURG",
+    "SIGXCPU",
+    "SIGXFSZ",
+    "SIGVTALRM",
+    "SIGPROF",
+    "SIGWINCH",
+    "SIGIO",
+    "SIGPWR",
+    "SIGSYS",
+];
+
+const X86_64_REGISTER_NAMES: &[&str] = &[
+    "r15", "r14", "r13", "r12", "rbp", "rbx", "r11", "r10", "r9", "r8", "rax", "rcx", "rdx", "rsi",
+    "rdi", "orig_rax", "rip", "cs", "eflags", "rsp", "ss", "fs_base", "gs_base", "ds", "es", "fs",
+    "gs",
+];
+
+const AARCH64_REGISTER_NAMES: &[&str] = &[
+    "x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7", "x8", "x9", "x10", "x11", "x12", "x13", "x14",
+    "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26", "x27",
+    "x28", "x29", "x30", "sp", "pc", "pstate",
+];
+
+/// Returns the names of the registers stored in `NT_PRSTATUS` notes for
+/// `machine`, or `None` when the register layout is not known.
+pub fn core_register_names(machine: ElfMachine) -> Option<&'static [&'static str]> {
+    match machine {
+        ElfMachine::X86_64 => Some(X86_64_REGISTER_NAMES),
+        ElfMachine::Aarch64 => Some(AARCH64_REGISTER_NAMES),
+        _ => None,
+    }
+}
+
+/// Returns the Linux name of signal `signo`.
+pub fn signal_name(signo: i32) -> Option<&'static str> {
+    SIGNAL_NAMES.get((signo as usize).wrapping_sub(1)).copied()
+}
+
+fn read_fixed_string(c: &mut ElfCursor, len: usize) -> Option<String> {
+    let bytes = c.bytes(len)?;
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    Some(String::from_utf8_lossy(&bytes[..end]).to_string())
+}
+
+impl<'data> ElfFile<'data> {
+    fn core_notes(&self, note_type: u32) -> impl Iterator<Item = ElfNote<'data>> {
+        self.notes()
+            .into_iter()
+            .filter(move |n| n.name == ELF_NOTE_CORE && n.note_type == note_type)
+    }
+
+    /// Returns the threads of a core dump in the order the kernel wrote
+    /// them, starting with the one that received the fatal signal.
+    pub fn core_threads(&self) -> Vec<ElfPrStatus> {
+        self.core_notes(NT_PRSTATUS)
+            .filter_map(|n| self.parse_prstatus(&n))
+            .collect()
+    }
+
+    fn parse_prstatus(&self, note: &ElfNote) -> Option<ElfPrStatus> {
+        let mut c = self.reader().cursor_over(note.desc);
+        let signo = c.u32()? as i32;
+        let code = c.u32()? as i32;
+        let errno = c.u32()? as i32;
+        let cursig = c.u16()?;
+        c.skip(2)?;
+        let sigpend = c.addr()?;
+        let sighold = c.addr()?;
+        let pid = c.u32()? as i32;
+        let ppid = c.u32()? as i32;
+        let pgrp = c.u32()? as i32;
+        let sid = c.u32()? as i32;
+        // pr_utime, pr_stime, pr_cutime and pr_cstime are two longs each.
+        let word_size = if self.reader().is_32bit() { 4 } else { 8 };
+        c.skip(4 * 2 * word_size)?;
+        let count = core_register_names(self.header.machine_type()).map_or(0, |names| names.len());
+        let registers = (0..count).map(|_| c.addr()).collect::<Option<Vec<_>>>()?;
+        Some(ElfPrStatus {
+            signo,
+            code,
+            errno,
+            cursig,
+            sigpend,
+            sighold,
+            pid,
+            ppid,
+            pgrp,
+            sid,
+            registers,
+        })
+    }
+
+    pub fn core_process_info(&self) -> Option<ElfPrPsInfo> {
+        let note = self.core_notes(NT_PRPSINFO).next()?;
+        let mut c = self.reader().cursor_over(note.desc);
+        let state = c.u8()?;
+        let sname = c.u8()?;
+        let zombie = c.u8()?;
+        let nice = c.u8()? as i8;
+        let is_32bit = self.reader().is_32bit();
+        if !is_32bit {
+            c.skip(4)?;
+        }
+        let flags = c.addr()?;
+        // The 32-bit ABIs use 16-bit uid_t and gid_t here.
+        let (uid, gid) = if is_32bit {
+            (c.u16()? as u32, c.u16()? as u32)
+        } else {
+            (c.u32()?, c.u32()?)
+        };
+        Some(ElfPrPsInfo {
+            state,
+            sname,
+            zombie,
+            nice,
+            flags,
+            uid,
+            gid,
+            pid: c.u32()? as i32,
+            ppid: c.u32()? as i32,
+            pgrp: c.u32()? as i32,
+            sid: c.u32()? as i32,
+            fname: read_fixed_string(&mut c, 16)?,
+            psargs: read_fixed_string(&mut c, 80)?,
+        })
+    }
+
+    pub fn core_signal_info(&self) -> Option<ElfSigInfo> {
+        let note = self.core_notes(NT_SIGINFO).next()?;
+        let mut c = self.reader().cursor_over(note.desc);
+        let signo = c.u32()? as i32;
+        let errno = c.u32()? as i32;
+        let code = c.u32()? as i32;
+        // The union of siginfo_t is pointer aligned.
+        if !self.reader().is_32bit() {
+            c.skip(4)?;
+        }
+        let fields = c.bytes(c.remaining())?;
+        let mut kill = self.reader().cursor_over(fields);
+        Some(ElfSigInfo {
+            signo,
+            errno,
+            code,
+            addr: self.reader().cursor_over(fields).addr()?,
+            pid: kill.u32()? as i32,
+            uid: kill.u32()?,
+        })
+    }
+
+    /// Returns the file mappings listed in the `NT_FILE` note.
+    pub fn core_mapped_files(&self) -> Vec<ElfMappedFile> {
+        self.core_notes(NT_FILE)
+            .next()
+            .and_then(|n| self.parse_mapped_files(&n))
+            .unwrap_or_default()
+    }
+
+    fn parse_mapped_files(&self, note: &ElfNote) -> Option<Vec<ElfMappedFile>> {
+        let mut c = self.reader().cursor_over(note.desc);
+        let count = c.addr()? as usize;
+        let page_size = c.addr()?;
+        // The table of ranges is followed by the NUL-terminated paths.
+        let word_size = if self.reader().is_32bit() { 4 } else { 8 };
+        let ranges = c.bytes(count.checked_mul(3 * word_size)?)?;
+        let mut ranges = self.reader().cursor_over(ranges);
+        let mut paths = c.bytes(c.remaining())?.split(|&b| b == 0);
+        (0..count)
+            .map(|_| {
+                Some(ElfMappedFile {
+                    start: ranges.addr()?,
+                    end: ranges.addr()?,
+                    offset: ranges.addr()?.wrapping_mul(page_size),
+                    path: String::from_utf8_lossy(paths.next()?).to_string(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the auxiliary vector from the `NT_AUXV` note, without the
+    /// terminating `AT_NULL` entry.
+    pub fn core_auxv(&self) -> Vec<ElfAuxvEntry> {
+        let note = match self.core_notes(NT_AUXV).next() {
+            Some(note) => note,
+            None => return Vec::new(),
+        };
+        let mut c = self.reader().cursor_over(note.desc);
+        let mut entries = Vec::new();
+        while let (Some(key), Some(value)) = (c.addr(), c.addr()) {
+            if key == AT_NULL {
+                break;
+            }
+            entries.push(ElfAuxvEntry { key, value });
+        }
+        entries
+    }
+
+    pub fn show_core(&self) {
+        if self.header.filetype != ET_CORE {
+            println!("This is not a core file.");
+            return;
+        }
+        if let Some(info) = self.core_process_info() {
+            self.show_core_process(&info);
+            println!();
+        }
+        if let Some(info) = self.core_signal_info() {
+            self.show_core_signal(&info);
+            println!();
+        }
+        for (i, thread) in self.core_threads().iter().enumerate() {
+            self.show_core_thread(i, thread);
+            println!();
+        }
+        self.show_core_mapped_files(&self.core_mapped_files());
+        println!();
+        self.show_core_auxv(&self.core_auxv());
+    }
+
+    fn show_core_process(&self, info: &ElfPrPsInfo) {
+        println!("Process:");
+        println!(" Command: {}", info.fname);
+        println!(" Arguments: {}", info.psargs);
+        println!(" State: {} ({})", info.state, info.sname as char);
+        println!(
+            " PID: {}, PPID: {}, PGRP: {}, SID: {}",
+            info.pid, info.ppid, info.pgrp, info.sid
+        );
+        println!(" UID: {}, GID: {}", info.uid, info.gid);
+        println!(" Flags: 0x{:x}, Nice: {}", info.flags, info.nice);
+    }
+
+    fn show_core_signal(&self, info: &ElfSigInfo) {
+        println!("Signal:");
+        println!(" Signal: {}", self.get_signal_name(info.signo));
+        println!(" Code: {}", self.get_signal_code(info.code));
+        println!(" Errno: {}", info.errno);
+        match (info.signo, info.code) {
+            (_, SI_USER) | (_, SI_QUEUE) | (_, SI_TKILL) => {
+                println!(" Sent by: PID {}, UID {}", info.pid, info.uid)
+            }
+            (SIGILL, _) | (SIGBUS, _) | (SIGFPE, _) | (SIGSEGV, _) => {
+                println!(" Fault address: 0x{:x}", info.addr)
+            }
+            _ => {}
+        }
+    }
+
+    fn show_core_thread(&self, index: usize, thread: &ElfPrStatus) {
+        println!("Thread {} (LWP {}):", index + 1, thread.pid);
+        println!(" Signal: {}", self.get_signal_name(thread.cursig as i32));
+        println!(
+            " Pending signals: 0x{:x}, Held signals: 0x{:x}",
+            thread.sigpend, thread.sighold
+        );
+        let names = match core_register_names(self.header.machine_type()) {
+            Some(names) => names,
+            None => {
+                println!(
+                    " Registers are not decoded for machine {}",
+                    self.header.machine_type()
+                );
+                return;
+            }
+        };
+        let mut table = Table::new();
+        table.set_titles(row!["Register", "Value"]);
+        for (name, value) in names.iter().zip(&thread.registers) {
+            table.add_row(row![name, format!("0x{:016x}", value)]);
+        }
+        table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+        table.printstd();
+    }
+
+    fn show_core_mapped_files(&self, files: &[ElfMappedFile]) {
+        if files.is_empty() {
+            println!("There are no mapped files in this core file.");
+            return;
+        }
+        println!("Mapped files:");
+
+        let mut table = Table::new();
+        table.set_titles(row!["Start", "End", "Offset", "Path"]);
+        for file in files {
+            table.add_row(row![
+                format!("0x{:x}", file.start),
+                format!("0x{:x}", file.end),
+                format!("0x{:x}", file.offset),
+                file.path,
+            ]);
+        }
+        table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+        table.printstd();
+    }
+
+    fn show_core_auxv(&self, auxv: &[ElfAuxvEntry]) {
+        if auxv.is_empty() {
+            println!("There is no auxiliary vector in this core file.");
+            return;
+        }
+        println!("Auxiliary vector:");
+
+        let mut table = Table::new();
+        table.set_titles(row!["Type", "Value"]);
+        for entry in auxv {
+            table.add_row(row![
+                self.get_auxv_type_name(entry.key),
+                self.get_auxv_value(entry),
+            ]);
+        }
+        table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+        table.printstd();
+    }
+
+    fn get_signal_name(&self, signo: i32) -> String {
+        match signal_name(signo) {
+            Some(name) => format!("{} ({})", name, signo),
+            None => signo.to_string(),
+        }
+    }
+
+    fn get_signal_code(&self, code: i32) -> String {
+        match code {
+            SI_USER => "SI_USER".to_string(),
+            SI_KERNEL => "SI_KERNEL".to_string(),
+            SI_QUEUE => "SI_QUEUE".to_string(),
+            SI_TKILL => "SI_TKILL".to_string(),
+            _ => code.to_string(),
+        }
+    }
+
+    fn get_auxv_type_name(&self, key: u64) -> String {
+        match key {
+            AT_IGNORE => "AT_IGNORE".to_string(),
+            AT_EXECFD => "AT_EXECFD".to_string(),
+            AT_PHDR => "AT_PHDR".to_string(),
+            AT_PHENT => "AT_PHENT".to_string(),
+            AT_PHNUM => "AT_PHNUM".to_string(),
+            AT_PAGESZ => "AT_PAGESZ".to_string(),
+            AT_BASE => "AT_BASE".to_string(),
+            AT_FLAGS => "AT_FLAGS".to_string(),
+            AT_ENTRY => "AT_ENTRY".to_string(),
+            AT_NOTELF => "AT_NOTELF".to_string(),
+            AT_UID => "AT_UID".to_string(),
+            AT_EUID => "AT_EUID".to_string(),
+            AT_GID => "AT_GID".to_string(),
+            AT_EGID => "AT_EGID".to_string(),
+            AT_PLATFORM => "AT_PLATFORM".to_string(),
+            AT_HWCAP => "AT_HWCAP".to_string(),
+            AT_CLKTCK => "AT_CLKTCK".to_string(),
+            AT_SECURE => "AT_SECURE".to_string(),
+            AT_BASE_PLATFORM => "AT_BASE_PLATFORM".to_string(),
+            AT_RANDOM => "AT_RANDOM".to_string(),
+            AT_HWCAP2 => "AT_HWCAP2".to_string(),
+            AT_RSEQ_FEATURE_SIZE => "AT_RSEQ_FEATURE_SIZE".to_string(),
+            AT_RSEQ_ALIGN => "AT_RSEQ_ALIGN".to_string(),
+            AT_HWCAP3 => "AT_HWCAP3".to_string(),
+            AT_HWCAP4 => "AT_HWCAP4".to_string(),
+            AT_EXECFN => "AT_EXECFN".to_string(),
+            AT_SYSINFO => "AT_SYSINFO".to_string(),
+            AT_SYSINFO_EHDR => "AT_SYSINFO_EHDR".to_string(),
+            AT_MINSIGSTKSZ => "AT_MINSIGSTKSZ".to_string(),
+            _ => format!("<unknown>: {}", key),
+        }
+    }
+
+    /// Formats an auxv value, reading the strings `AT_PLATFORM` and
+    /// `AT_EXECFN` point at from the dumped memory.
+    fn get_auxv_value(&self, entry: &ElfAuxvEntry) -> String {
+        match entry.key {
+            AT_PHENT | AT_PHNUM | AT_PAGESZ | AT_UID | AT_EUID | AT_GID | AT_EGID | AT_CLKTCK
+            | AT_SECURE | AT_EXECFD | AT_RSEQ_FEATURE_SIZE | AT_RSEQ_ALIGN | AT_MINSIGSTKSZ => {
+                entry.value.to_string()
+            }
+            AT_PLATFORM | AT_BASE_PLATFORM | AT_EXECFN => {
+                match self.read_core_string(entry.value) {
+                    Some(s) => format!("0x{:x} \"{}\"", entry.value, s),
+                    None => format!("0x{:x}", entry.value),
+                }
+            }
+            _ => format!("0x{:x}", entry.value),
+        }
+    }
+
+    fn read_core_string(&self, addr: u64) -> Option<String> {
+        let offset = self.virtual_to_offset(addr)? as usize;
+        let bytes = self.data.get(offset..)?;
+        let len = bytes.iter().position(|&b| b == 0)?;
+        Some(String::from_utf8_lossy(&bytes[..len]).to_string())
+    }
+}
@@ -0,0 +1,382 @@
+use crate::elf::dynamic::{ElfDynamic, DT_CHECKSUM, DT_GNU_PRELINKED};
+use crate::elf::elf_header::{EI_ABIVERSION, EI_CLASS, EI_DATA};
+use crate::elf::section_header::{ElfSectionHeader, SHT_DYNAMIC};
+use crate::elf::*;
+
+use prettytable::{format, row, Table};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+
+/// The part of the files a difference was found in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ElfDiffKind {
+    Header,
+    Section,
+    Segment,
+    Symbol,
+    BuildId,
+}
+
+/// A single difference between two ELF files.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElfDifference {
+    pub kind: ElfDiffKind,
+    /// The header field, section, segment or symbol that differs.
+    pub name: String,
+    /// The field that changed, or "added" and "removed" for items that are
+    /// only in one of the files.
+    pub change: String,
+    pub old: String,
+    pub new: String,
+}
+
+const BUILD_ID_SECTION: &str = ".note.gnu.build-id";
+/// Holds the CRC of the separate debug file, which changes with the build ID.
+const DEBUGLINK_SECTION: &str = ".gnu_debuglink";
+
+/// Dynamic entries holding prelink timestamps and checksums.
+const NOISE_DYNAMIC_TAGS: &[i64] = &[DT_GNU_PRELINKED, DT_CHECKSUM];
+
+impl fmt::Display for ElfDiffKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ElfDiffKind::Header => write!(f, "header"),
+            ElfDiffKind::Section => write!(f, "section"),
+            ElfDiffKind::Segment => write!(f, "segment"),
+            ElfDiffKind::Symbol => write!(f, "symbol"),
+            ElfDiffKind::BuildId => write!(f, "build-id"),
+        }
+    }
+}
+
+/// Pairs up the items of two files by key. Repeated keys are matched in
+/// order, so the n-th `LOAD` segment of one file is compared with the n-th
+/// of the other.
+fn match_by_key<K, T>(old: Vec<(K, T)>, new: Vec<(K, T)>) -> Vec<(K, Option<T>, Option<T>)>
+where
+    K: Clone + Eq + Hash,
+{
+    fn number<K: Clone + Eq + Hash, T>(items: Vec<(K, T)>) -> Vec<((K, usize), T)> {
+        let mut seen: HashMap<K, usize> = HashMap::new();
+        items
+            .into_iter()
+            .map(|(key, item)| {
+                let count = seen.entry(key.clone()).or_insert(0);
+                *count += 1;
+                ((key, *count), item)
+            })
+            .collect()
+    }
+
+    let mut new: Vec<_> = number(new).into_iter().map(Some).collect();
+    let positions: HashMap<(K, usize), usize> = new
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item)| Some((item.as_ref()?.0.clone(), i)))
+        .collect();
+    let mut matched = Vec::new();
+    for (key, item) in number(old) {
+        let other = positions
+            .get(&key)
+            .and_then(|&i| new[i].take())
+            .map(|(_, item)| item);
+        matched.push((key.0, Some(item), other));
+    }
+    matched.extend(
+        new.into_iter()
+            .flatten()
+            .map(|((key, _), item)| (key, None, Some(item))),
+    );
+    matched
+}
+
+struct ElfDiff {
+    differences: Vec<ElfDifference>,
+}
+
+impl ElfDiff {
+    fn push(&mut self, kind: ElfDiffKind, name: &str, change: &str, old: String, new: String) {
+        self.differences.push(ElfDifference {
+            kind,
+            name: name.to_string(),
+            change: change.to_string(),
+            old,
+            new,
+        });
+    }
+
+    fn compare(&mut self, kind: ElfDiffKind, name: &str, change: &str, old: String, new: String) {
+        if old != new {
+            self.push(kind, name, change, old, new);
+        }
+    }
+
+    fn added(&mut self, kind: ElfDiffKind, name: &str, new: String) {
+        self.push(kind, name, "added", String::new(), new);
+    }
+
+    fn removed(&mut self, kind: ElfDiffKind, name: &str, old: String) {
+        self.push(kind, name, "removed", old, String::new());
+    }
+}
+
+impl<'data> ElfFile<'data> {
+    /// Compares this file with `other`: the ELF header, sections and
+    /// segments matched by name and type, symbols matched by name, and the
+    /// build ID. With `ignore_noise`, the build ID, the debug link CRC and
+    /// prelink timestamps are skipped so that reproducible builds compare
+    /// equal.
+    pub fn diff(&self, other: &ElfFile, ignore_noise: bool) -> Vec<ElfDifference> {
+        let mut diff = ElfDiff {
+            differences: Vec::new(),
+        };
+        self.diff_header(other, &mut diff);
+        self.diff_sections(other, ignore_noise, &mut diff);
+        self.diff_segments(other, &mut diff);
+        self.diff_symbols(other, &mut diff);
+        if !ignore_noise {
+            let hex = |id: Option<&[u8]>| {
+                id.map_or(String::new(), |id| {
+                    id.iter().map(|b| format!("{:02x}", b)).collect()
+                })
+            };
+            diff.compare(
+                ElfDiffKind::BuildId,
+                "build-id",
+                "changed",
+                hex(self.build_id()),
+                hex(other.build_id()),
+            );
+        }
+        diff.differences
+    }
+
+    fn diff_header(&self, other: &ElfFile, diff: &mut ElfDiff) {
+        let fields = |elf: &ElfFile| {
+            let header = &elf.header;
+            vec![
+                ("class", header.ident[EI_CLASS].to_string()),
+                ("data", header.ident[EI_DATA].to_string()),
+                ("osabi", header.osabi().to_string()),
+                ("abiversion", header.ident[EI_ABIVERSION].to_string()),
+                ("type", header.file_type().to_string()),
+                ("machine", header.machine_type().to_string()),
+                ("version", format!("0x{:x}", header.version)),
+                ("entry", format!("0x{:x}", header.entrypoint)),
+                ("flags", format!("0x{:x}", header.flags)),
+                ("program headers", elf.program_headers.len().to_string()),
+                ("section headers", elf.section_headers.len().to_string()),
+            ]
+        };
+        for ((field, old), (_, new)) in fields(self).into_iter().zip(fields(other)) {
+            diff.compare(ElfDiffKind::Header, field, "changed", old, new);
+        }
+    }
+
+    fn diff_sections(&self, other: &ElfFile, ignore_noise: bool, diff: &mut ElfDiff) {
+        let sections = |elf: &ElfFile| {
+            elf.section_headers
+                .iter()
+                .skip(1)
+                .map(|sh| ((elf.get_section_name(&sh), sh.section_type), sh))
+                .collect::<Vec<_>>()
+        };
+        // Sections of the same name but different types are not the same
+        // section, so one is reported as removed and the other as added.
+        let kind = ElfDiffKind::Section;
+        for ((name, _), a, b) in match_by_key(sections(self), sections(other)) {
+            let (a, b) = match (a, b) {
+                (Some(a), Some(b)) => (a, b),
+                (Some(a), None) => {
+                    diff.removed(kind, &name, format!("0x{:x}", a.size));
+                    continue;
+                }
+                (None, Some(b)) => {
+                    diff.added(kind, &name, format!("0x{:x}", b.size));
+                    continue;
+                }
+                (None, None) => continue,
+            };
+            diff.compare(
+                kind,
+                &name,
+                "flags",
+                self.get_section_flags(a.flags).trim().to_string(),
+                other.get_section_flags(b.flags).trim().to_string(),
+            );
+            diff.compare(
+                kind,
+                &name,
+                "address",
+                format!("0x{:x}", a.addr),
+                format!("0x{:x}", b.addr),
+            );
+            diff.compare(
+                kind,
+                &name,
+                "size",
+                format!("0x{:x}", a.size),
+                format!("0x{:x}", b.size),
+            );
+            diff.compare(
+                kind,
+                &name,
+                "entsize",
+                format!("0x{:x}", a.entry_size),
+                format!("0x{:x}", b.entry_size),
+            );
+            diff.compare(
+                kind,
+                &name,
+                "align",
+                a.alignment.to_string(),
+                b.alignment.to_string(),
+            );
+            // The build ID is reported on its own.
+            let noise = name == BUILD_ID_SECTION || (ignore_noise && name == DEBUGLINK_SECTION);
+            if a.size == b.size
+                && !noise
+                && !self.section_contents_match(other, &a, &b, ignore_noise)
+            {
+                diff.push(kind, &name, "contents", String::new(), String::new());
+            }
+        }
+    }
+
+    fn section_contents_match(
+        &self,
+        other: &ElfFile,
+        a: &ElfSectionHeader,
+        b: &ElfSectionHeader,
+        ignore_noise: bool,
+    ) -> bool {
+        if ignore_noise && a.section_type == SHT_DYNAMIC {
+            let entries = |elf: &ElfFile| {
+                elf.dynamic_entries()
+                    .into_iter()
+                    .filter(|d| !NOISE_DYNAMIC_TAGS.contains(&d.tag))
+                    .map(|ElfDynamic { tag, value }| (tag, value))
+                    .collect::<Vec<_>>()
+            };
+            return entries(self) == entries(other);
+        }
+        self.section_data(a) == other.section_data(b)
+    }
+
+    fn diff_segments(&self, other: &ElfFile, diff: &mut ElfDiff) {
+        let segments = |elf: &ElfFile| {
+            elf.program_headers
+                .iter()
+                .map(|ph| (elf.get_segment_type(ph.segment_type), ph))
+                .collect::<Vec<_>>()
+        };
+        let kind = ElfDiffKind::Segment;
+        for (name, a, b) in match_by_key(segments(self), segments(other)) {
+            let (a, b) = match (a, b) {
+                (Some(a), Some(b)) => (a, b),
+                (Some(a), None) => {
+                    diff.removed(kind, &name, format!("0x{:x}", a.memory_size));
+                    continue;
+                }
+                (None, Some(b)) => {
+                    diff.added(kind, &name, format!("0x{:x}", b.memory_size));
+                    continue;
+                }
+                (None, None) => continue,
+            };
+            diff.compare(
+                kind,
+                &name,
+                "flags",
+                self.get_segment_flags(a.flags),
+                other.get_segment_flags(b.flags),
+            );
+            diff.compare(
+                kind,
+                &name,
+                "address",
+                format!("0x{:x}", a.virtual_addr),
+                format!("0x{:x}", b.virtual_addr),
+            );
+            diff.compare(
+                kind,
+                &name,
+                "filesize",
+                format!("0x{:x}", a.file_size),
+                format!("0x{:x}", b.file_size),
+            );
+            diff.compare(
+                kind,
+                &name,
+                "memsize",
+                format!("0x{:x}", a.memory_size),
+                format!("0x{:x}", b.memory_size),
+            );
+            diff.compare(
+                kind,
+                &name,
+                "align",
+                format!("0x{:x}", a.alignment),
+                format!("0x{:x}", b.alignment),
+            );
+        }
+    }
+
+    /// Matches symbols by name within the symbol table of the same name and
+    /// reports the ones added, removed or resized.
+    fn diff_symbols(&self, other: &ElfFile, diff: &mut ElfDiff) {
+        let symbols = |elf: &ElfFile| {
+            let mut symbols = Vec::new();
+            for st in &elf.symbol_tables {
+                let section = match elf.section_headers.get(st.index) {
+                    Some(section) => section,
+                    None => continue,
+                };
+                let strtab = match elf.get_linked_section(&section) {
+                    Some(strtab) => strtab,
+                    None => continue,
+                };
+                let table = elf.get_section_name(&section);
+                for symbol in st.symbols.iter() {
+                    let name = elf.get_name_from_strtab(&strtab, symbol.name as usize);
+                    if !name.is_empty() {
+                        symbols.push((format!("{} ({})", name, table), symbol.size));
+                    }
+                }
+            }
+            symbols
+        };
+        let kind = ElfDiffKind::Symbol;
+        for (name, a, b) in match_by_key(symbols(self), symbols(other)) {
+            match (a, b) {
+                (Some(a), Some(b)) => {
+                    diff.compare(kind, &name, "size", a.to_string(), b.to_string())
+                }
+                (Some(a), None) => diff.removed(kind, &name, a.to_string()),
+                (None, Some(b)) => diff.added(kind, &name, b.to_string()),
+                (None, None) => {}
+            }
+        }
+    }
+
+    /// Prints the differences from `other` and returns them.
+    pub fn show_diff(&self, other: &ElfFile, ignore_noise: bool) -> Vec<ElfDifference> {
+        let differences = self.diff(other, ignore_noise);
+        if differences.is_empty() {
+            println!("No structural differences.");
+            return differences;
+        }
+        let count = differences.len();
+        println!("{} difference{}:", count, if count == 1 { "" } else { "s" });
+
+        let mut table = Table::new();
+        table.set_titles(row!["Kind", "Name", "Change", "Old", "New"]);
+        for d in &differences {
+            table.add_row(row![d.kind, d.name, d.change, d.old, d.new]);
+        }
+        table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+        table.printstd();
+        differences
+    }
+}
@@ -0,0 +1,111 @@
+use crate::elf::*;
+
+use std::borrow::Cow;
+
+const HEX_DUMP_LINE: usize = 16;
+
+impl ElfFile<'_> {
+    /// Prints the bytes of the section named or numbered `spec`, in the
+    /// layout of `readelf -x`. Addresses start at `sh_addr`. With
+    /// `decompress`, compressed sections are dumped after decompression.
+    pub fn show_hex_dump(&self, spec: &str, decompress: bool) {
+        let (name, sh, data) = match self.get_dump_section(spec, decompress) {
+            Some(section) => section,
+            None => return,
+        };
+        println!("Hex dump of section '{}':", name);
+
+        for (i, line) in data.chunks(HEX_DUMP_LINE).enumerate() {
+            let address = sh.addr.wrapping_add((i * HEX_DUMP_LINE) as u64);
+            let hex: Vec<String> = line
+                .chunks(4)
+                .map(|word| word.iter().map(|b| format!("{:02x}", b)).collect())
+                .collect();
+            let ascii: String = line
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            println!("  0x{:08x} {:<35} {}", address, hex.join(" "), ascii);
+        }
+    }
+
+    /// Prints the NUL-terminated strings of the section named or numbered
+    /// `spec` with their offsets, in the layout of `readelf -p`.
+    pub fn show_string_dump(&self, spec: &str, decompress: bool) {
+        let (name, _, data) = match self.get_dump_section(spec, decompress) {
+            Some(section) => section,
+            None => return,
+        };
+        println!("String dump of section '{}':", name);
+
+        let mut found = false;
+        let mut offset = 0;
+        while offset < data.len() {
+            if data[offset] == 0 {
+                offset += 1;
+                continue;
+            }
+            let len = data[offset..]
+                .iter()
+                .position(|&b| b == 0)
+                .unwrap_or(data.len() - offset);
+            let string: String = data[offset..offset + len]
+                .iter()
+                .map(|&b| b as char)
+                .collect();
+            println!("  [{:6x}]  {}", offset, string);
+            found = true;
+            offset += len + 1;
+        }
+        if !found {
+            println!("  No strings found in this section.");
+        }
+    }
+
+    fn get_dump_section(
+        &self,
+        spec: &str,
+        decompress: bool,
+    ) -> Option<(String, ElfSectionHeader, Cow<'_, [u8]>)> {
+        let (index, sh) = match self.find_section(spec) {
+            Some(section) => section,
+            None => {
+                eprintln!(
+                    "readelf: Warning: Section '{}' was not dumped because it does not exist",
+                    spec
+                );
+                return None;
+            }
+        };
+        let name = self.get_section_name(&sh);
+        if sh.section_type == SHT_NOBITS || sh.size == 0 {
+            println!("Section '{}' has no data to dump.", name);
+            return None;
+        }
+        if decompress {
+            return match self.decompressed_section_data(index) {
+                Ok(data) => Some((name, sh, data)),
+                Err(e) => {
+                    eprintln!("readelf: Warning: {}", e);
+                    None
+                }
+            };
+        }
+        match self.section_data(&sh) {
+            Some(data) => Some((name, sh, Cow::Borrowed(data))),
+            None => {
+                eprintln!(
+                    "readelf: Warning: Section '{}' extends beyond the end of the file",
+                    name
+                );
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,437 @@
+use crate::elf::common::get_flag_names;
+use crate::elf::reader::ElfReader;
+use crate::elf::table::LazyTable;
+use crate::elf::writer::ElfWriteCursor;
+use crate::elf::*;
+
+use prettytable::{format, row, Table};
+
+/// The dynamic section, found through `SHT_DYNAMIC` or, for files without
+/// section headers, through `PT_DYNAMIC`.
+#[derive(Debug)]
+pub struct ElfDynamicTable<'data> {
+    pub index: Option<usize>,
+    pub offset: u64,
+    pub entries: LazyTable<'data, ElfDynamic>,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ElfDynamic {
+    pub tag: ElfSxword,
+    pub value: ElfXword,
+}
+
+pub const ELF32_DYNAMIC_SIZE: usize = 8;
+pub const ELF64_DYNAMIC_SIZE: usize = 16;
+
+pub const DT_NULL: i64 = 0;
+pub const DT_NEEDED: i64 = 1;
+pub const DT_PLTRELSZ: i64 = 2;
+pub const DT_PLTGOT: i64 = 3;
+pub const DT_HASH: i64 = 4;
+pub const DT_STRTAB: i64 = 5;
+pub const DT_SYMTAB: i64 = 6;
+pub const DT_RELA: i64 = 7;
+pub const DT_RELASZ: i64 = 8;
+pub const DT_RELAENT: i64 = 9;
+pub const DT_STRSZ: i64 = 10;
+pub const DT_SYMENT: i64 = 11;
+pub const DT_INIT: i64 = 12;
+pub const DT_FINI: i64 = 13;
+pub const DT_SONAME: i64 = 14;
+pub const DT_RPATH: i64 = 15;
+pub const DT_SYMBOLIC: i64 = 16;
+pub const DT_REL: i64 = 17;
+pub const DT_RELSZ: i64 = 18;
+pub const DT_RELENT: i64 = 19;
+pub const DT_PLTREL: i64 = 20;
+pub const DT_DEBUG: i64 = 21;
+pub const DT_TEXTREL: i64 = 22;
+pub const DT_JMPREL: i64 = 23;
+pub const DT_BIND_NOW: i64 = 24;
+pub const DT_INIT_ARRAY: i64 = 25;
+pub const DT_FINI_ARRAY: i64 = 26;
+pub const DT_INIT_ARRAYSZ: i64 = 27;
+pub const DT_FINI_ARRAYSZ: i64 = 28;
+pub const DT_RUNPATH: i64 = 29;
+pub const DT_FLAGS: i64 = 30;
+pub const DT_PREINIT_ARRAY: i64 = 32;
+pub const DT_PREINIT_ARRAYSZ: i64 = 33;
+pub const DT_SYMTAB_SHNDX: i64 = 34;
+pub const DT_RELRSZ: i64 = 35;
+pub const DT_RELR: i64 = 36;
+pub const DT_RELRENT: i64 = 37;
+pub const DT_GNU_PRELINKED: i64 = 0x6ffffdf5;
+pub const DT_GNU_CONFLICTSZ: i64 = 0x6ffffdf6;
+pub const DT_GNU_LIBLISTSZ: i64 = 0x6ffffdf7;
+pub const DT_CHECKSUM: i64 = 0x6ffffdf8;
+pub const DT_PLTPADSZ: i64 = 0x6ffffdf9;
+pub const DT_MOVEENT: i64 = 0x6ffffdfa;
+pub const DT_MOVESZ: i64 = 0x6ffffdfb;
+pub const DT_FEATURE: i64 = 0x6ffffdfc;
+pub const DT_POSFLAG_1: i64 = 0x6ffffdfd;
+pub const DT_SYMINSZ: i64 = 0x6ffffdfe;
+pub const DT_SYMINENT: i64 = 0x6ffffdff;
+pub const DT_GNU_HASH: i64 = 0x6ffffef5;
+pub const DT_TLSDESC_PLT: i64 = 0x6ffffef6;
+pub const DT_TLSDESC_GOT: i64 = 0x6ffffef7;
+pub const DT_GNU_CONFLICT: i64 = 0x6ffffef8;
+pub const DT_GNU_LIBLIST: i64 = 0x6ffffef9;
+pub const DT_CONFIG: i64 = 0x6ffffefa;
+pub const DT_DEPAUDIT: i64 = 0x6ffffefb;
+pub const DT_AUDIT: i64 = 0x6ffffefc;
+pub const DT_PLTPAD: i64 = 0x6ffffefd;
+pub const DT_MOVETAB: i64 = 0x6ffffefe;
+pub const DT_SYMINFO: i64 = 0x6ffffeff;
+pub const DT_VERSYM: i64 = 0x6ffffff0;
+pub const DT_RELACOUNT: i64 = 0x6ffffff9;
+pub const DT_RELCOUNT: i64 = 0x6ffffffa;
+pub const DT_FLAGS_1: i64 = 0x6ffffffb;
+pub const DT_VERDEF: i64 = 0x6ffffffc;
+pub const DT_VERDEFNUM: i64 = 0x6ffffffd;
+pub const DT_VERNEED: i64 = 0x6ffffffe;
+pub const DT_VERNEEDNUM: i64 = 0x6fffffff;
+pub const DT_AUXILIARY: i64 = 0x7ffffffd;
+pub const DT_FILTER: i64 = 0x7fffffff;
+
+pub const DF_ORIGIN: u64 = 1 << 0;
+pub const DF_SYMBOLIC: u64 = 1 << 1;
+pub const DF_TEXTREL: u64 = 1 << 2;
+pub const DF_BIND_NOW: u64 = 1 << 3;
+pub const DF_STATIC_TLS: u64 = 1 << 4;
+
+pub const DF_1_NOW: u64 = 1 << 0;
+pub const DF_1_GLOBAL: u64 = 1 << 1;
+pub const DF_1_GROUP: u64 = 1 << 2;
+pub const DF_1_NODELETE: u64 = 1 << 3;
+pub const DF_1_LOADFLTR: u64 = 1 << 4;
+pub const DF_1_INITFIRST: u64 = 1 << 5;
+pub const DF_1_NOOPEN: u64 = 1 << 6;
+pub const DF_1_ORIGIN: u64 = 1 << 7;
+pub const DF_1_DIRECT: u64 = 1 << 8;
+pub const DF_1_TRANS: u64 = 1 << 9;
+pub const DF_1_INTERPOSE: u64 = 1 << 10;
+pub const DF_1_NODEFLIB: u64 = 1 << 11;
+pub const DF_1_NODUMP: u64 = 1 << 12;
+pub const DF_1_CONFALT: u64 = 1 << 13;
+pub const DF_1_ENDFILTEE: u64 = 1 << 14;
+pub const DF_1_DISPRELDNE: u64 = 1 << 15;
+pub const DF_1_DISPRELPND: u64 = 1 << 16;
+pub const DF_1_NODIRECT: u64 = 1 << 17;
+pub const DF_1_IGNMULDEF: u64 = 1 << 18;
+pub const DF_1_NOKSYMS: u64 = 1 << 19;
+pub const DF_1_NOHDR: u64 = 1 << 20;
+pub const DF_1_EDITED: u64 = 1 << 21;
+pub const DF_1_NORELOC: u64 = 1 << 22;
+pub const DF_1_SYMINTPOSE: u64 = 1 << 23;
+pub const DF_1_GLOBAUDIT: u64 = 1 << 24;
+pub const DF_1_SINGLETON: u64 = 1 << 25;
+pub const DF_1_STUB: u64 = 1 << 26;
+pub const DF_1_PIE: u64 = 1 << 27;
+pub const DF_1_KMOD: u64 = 1 << 28;
+pub const DF_1_WEAKFILTER: u64 = 1 << 29;
+pub const DF_1_NOCOMMON: u64 = 1 << 30;
+
+pub const DF_P1_LAZYLOAD: u64 = 1 << 0;
+pub const DF_P1_GROUPPERM: u64 = 1 << 1;
+
+pub const DTF_1_PARINIT: u64 = 1 << 0;
+pub const DTF_1_CONFEXP: u64 = 1 << 1;
+
+const DF_NAMES: &[(u64, &str)] = &[
+    (DF_ORIGIN, "ORIGIN"),
+    (DF_SYMBOLIC, "SYMBOLIC"),
+    (DF_TEXTREL, "TEXTREL"),
+    (DF_BIND_NOW, "BIND_NOW"),
+    (DF_STATIC_TLS, "STATIC_TLS"),
+];
+
+const DF_1_NAMES: &[(u64, &str)] = &[
+    (DF_1_NOW, "NOW"),
+    (DF_1_GLOBAL, "GLOBAL"),
+    (DF_1_GROUP, "GROUP"),
+    (DF_1_NODELETE, "NODELETE"),
+    (DF_1_LOADFLTR, "LOADFLTR"),
+    (DF_1_INITFIRST, "INITFIRST"),
+    (DF_1_NOOPEN, "NOOPEN"),
+    (DF_1_ORIGIN, "ORIGIN"),
+    (DF_1_DIRECT, "DIRECT"),
+    (DF_1_TRANS, "TRANS"),
+    (DF_1_INTERPOSE, "INTERPOSE"),
+    (DF_1_NODEFLIB, "NODEFLIB"),
+    (DF_1_NODUMP, "NODUMP"),
+    (DF_1_CONFALT, "CONFALT"),
+    (DF_1_ENDFILTEE, "ENDFILTEE"),
+    (DF_1_DISPRELDNE, "DISPRELDNE"),
+    (DF_1_DISPRELPND, "DISPRELPND"),
+    (DF_1_NODIRECT, "NODIRECT"),
+    (DF_1_IGNMULDEF, "IGNMULDEF"),
+    (DF_1_NOKSYMS, "NOKSYMS"),
+    (DF_1_NOHDR, "NOHDR"),
+    (DF_1_EDITED, "EDITED"),
+    (DF_1_NORELOC, "NORELOC"),
+    (DF_1_SYMINTPOSE, "SYMINTPOSE"),
+    (DF_1_GLOBAUDIT, "GLOBAUDIT"),
+    (DF_1_SINGLETON, "SINGLETON"),
+    (DF_1_STUB, "STUB"),
+    (DF_1_PIE, "PIE"),
+    (DF_1_KMOD, "KMOD"),
+    (DF_1_WEAKFILTER, "WEAKFILTER"),
+    (DF_1_NOCOMMON, "NOCOMMON"),
+];
+
+const DF_P1_NAMES: &[(u64, &str)] = &[(DF_P1_LAZYLOAD, "LAZYLOAD"), (DF_P1_GROUPPERM, "GROUPPERM")];
+
+const DTF_1_NAMES: &[(u64, &str)] = &[(DTF_1_PARINIT, "PARINIT"), (DTF_1_CONFEXP, "CONFEXP")];
+
+impl ElfDynamic {
+    pub(crate) fn entry_size(reader: &ElfReader) -> usize {
+        if reader.is_32bit() {
+            ELF32_DYNAMIC_SIZE
+        } else {
+            ELF64_DYNAMIC_SIZE
+        }
+    }
+
+    pub(crate) fn parse(reader: &ElfReader, offset: usize) -> Option<Self> {
+        let mut c = reader.cursor(offset, Self::entry_size(reader))?;
+        Some(Self {
+            tag: c.saddr()?,
+            value: c.addr()?,
+        })
+    }
+
+    /// Returns whether the value is an offset into the dynamic string table.
+    pub fn has_string_value(&self) -> bool {
+        matches!(
+            self.tag,
+            DT_NEEDED
+                | DT_SONAME
+                | DT_RPATH
+                | DT_RUNPATH
+                | DT_AUXILIARY
+                | DT_FILTER
+                | DT_CONFIG
+                | DT_DEPAUDIT
+                | DT_AUDIT
+        )
+    }
+
+    pub(crate) fn write(&self, c: &mut ElfWriteCursor) {
+        c.addr(self.tag as u64);
+        c.addr(self.value);
+    }
+}
+
+impl<'data> ElfDynamicTable<'data> {
+    /// Iterates over the entries up to, but not including, `DT_NULL`.
+    pub fn iter(&self) -> impl Iterator<Item = ElfDynamic> + 'data {
+        self.entries.iter().take_while(|d| d.tag != DT_NULL)
+    }
+}
+
+impl ElfFile<'_> {
+    pub fn dynamic_entries(&self) -> Vec<ElfDynamic> {
+        match &self.dynamic_table {
+            Some(dt) => dt.iter().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn get_dynamic_value(&self, tag: i64) -> Option<u64> {
+        self.dynamic_entries()
+            .into_iter()
+            .find(|d| d.tag == tag)
+            .map(|d| d.value)
+    }
+
+    /// Resolves an offset into the dynamic string table. Like binutils, this
+    /// follows `DT_STRTAB`/`DT_STRSZ` and falls back to the dynamic section's
+    /// `sh_link`.
+    pub fn get_dynamic_string(&self, index: u64) -> Option<String> {
+        let strtab = self
+            .get_dynamic_value(DT_STRTAB)
+            .and_then(|addr| self.virtual_to_offset(addr));
+        let (offset, size) = match strtab {
+            Some(offset) => {
+                let size = self
+                    .get_dynamic_value(DT_STRSZ)
+                    .unwrap_or((self.data.len() as u64).saturating_sub(offset));
+                (offset, size)
+            }
+            None => {
+                let dynamic = self
+                    .section_headers
+                    .get(self.dynamic_table.as_ref()?.index?)?;
+                let strtab = self.get_linked_section(&dynamic)?;
+                (strtab.offset, strtab.size)
+            }
+        };
+        if index >= size {
+            return None;
+        }
+        Some(self.get_string(offset as usize, size as usize, index as usize))
+    }
+
+    pub fn soname(&self) -> Option<String> {
+        self.get_dynamic_string(self.get_dynamic_value(DT_SONAME)?)
+    }
+
+    pub fn rpath(&self) -> Option<String> {
+        self.get_dynamic_string(self.get_dynamic_value(DT_RPATH)?)
+    }
+
+    pub fn runpath(&self) -> Option<String> {
+        self.get_dynamic_string(self.get_dynamic_value(DT_RUNPATH)?)
+    }
+
+    pub fn needed_libraries(&self) -> Vec<String> {
+        self.dynamic_entries()
+            .iter()
+            .filter(|d| d.tag == DT_NEEDED)
+            .filter_map(|d| self.get_dynamic_string(d.value))
+            .collect()
+    }
+
+    pub fn show_dynamic(&self) {
+        self.warn_table_errors(ElfTable::Dynamic);
+        let dt = match &self.dynamic_table {
+            Some(dt) => dt,
+            None => {
+                println!("There is no dynamic section in this file.");
+                return;
+            }
+        };
+        let entries = dt.iter().count();
+        let count = if entries < dt.entries.len() {
+            entries + 1
+        } else {
+            entries
+        };
+        println!(
+            "Dynamic section at offset 0x{:x} contains {} entries:",
+            dt.offset, count
+        );
+
+        let mut table = Table::new();
+        table.set_titles(row!["Tag", "Type", "Name/Value"]);
+        for d in dt.entries.iter().take(count) {
+            let tag = if self.is_32bit() {
+                format!("0x{:08x}", d.tag as u32)
+            } else {
+                format!("0x{:016x}", d.tag)
+            };
+            table.add_row(row![
+                tag,
+                format!("({})", self.get_dynamic_tag_name(d.tag)),
+                self.get_dynamic_value_string(&d),
+            ]);
+        }
+        table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+        table.printstd();
+    }
+
+    fn get_dynamic_value_string(&self, d: &ElfDynamic) -> String {
+        let string = || self.get_dynamic_string(d.value).unwrap_or_default();
+        match d.tag {
+            DT_NEEDED => format!("Shared library: [{}]", string()),
+            DT_SONAME => format!("Library soname: [{}]", string()),
+            DT_RPATH => format!("Library rpath: [{}]", string()),
+            DT_RUNPATH => format!("Library runpath: [{}]", string()),
+            DT_AUXILIARY => format!("Auxiliary library: [{}]", string()),
+            DT_FILTER => format!("Filter library: [{}]", string()),
+            DT_CONFIG => format!("Configuration file: [{}]", string()),
+            DT_DEPAUDIT => format!("Dependency audit library: [{}]", string()),
+            DT_AUDIT => format!("Audit library: [{}]", string()),
+            DT_FLAGS => get_flag_names(d.value, DF_NAMES, " "),
+            DT_FLAGS_1 => format!("Flags: {}", get_flag_names(d.value, DF_1_NAMES, " ")),
+            DT_POSFLAG_1 => format!("Flags: {}", get_flag_names(d.value, DF_P1_NAMES, " ")),
+            DT_FEATURE => format!("Flags: {}", get_flag_names(d.value, DTF_1_NAMES, " ")),
+            DT_PLTREL => self.get_dynamic_tag_name(d.value as i64),
+            DT_BIND_NOW | DT_SYMBOLIC | DT_TEXTREL => String::new(),
+            DT_PLTRELSZ | DT_RELASZ | DT_RELAENT | DT_STRSZ | DT_SYMENT | DT_RELSZ | DT_RELENT
+            | DT_INIT_ARRAYSZ | DT_FINI_ARRAYSZ | DT_PREINIT_ARRAYSZ | DT_RELRSZ | DT_RELRENT
+            | DT_GNU_CONFLICTSZ | DT_GNU_LIBLISTSZ | DT_PLTPADSZ | DT_MOVEENT | DT_MOVESZ
+            | DT_SYMINSZ | DT_SYMINENT => format!("{} (bytes)", d.value),
+            DT_VERDEFNUM | DT_VERNEEDNUM | DT_RELACOUNT | DT_RELCOUNT => {
+                format!("{}", d.value)
+            }
+            _ => format!("0x{:x}", d.value),
+        }
+    }
+
+    fn get_dynamic_tag_name(&self, tag: i64) -> String {
+        match tag {
+            DT_NULL => "NULL".to_string(),
+            DT_NEEDED => "NEEDED".to_string(),
+            DT_PLTRELSZ => "PLTRELSZ".to_string(),
+            DT_PLTGOT => "PLTGOT".to_string(),
+            DT_HASH => "HASH".to_string(),
+            DT_STRTAB => "STRTAB".to_string(),
+            DT_SYMTAB => "SYMTAB".to_string(),
+            DT_RELA => "RELA".to_string(),
+            DT_RELASZ => "RELASZ".to_string(),
+            DT_RELAENT => "RELAENT".to_string(),
+            DT_STRSZ => "STRSZ".to_string(),
+            DT_SYMENT => "SYMENT".to_string(),
+            DT_INIT => "INIT".to_string(),
+            DT_FINI => "FINI".to_string(),
+            DT_SONAME => "SONAME".to_string(),
+            DT_RPATH => "RPATH".to_string(),
+            DT_SYMBOLIC => "SYMBOLIC".to_string(),
+            DT_REL => "REL".to_string(),
+            DT_RELSZ => "RELSZ".to_string(),
+            DT_RELENT => "RELENT".to_string(),
+            DT_PLTREL => "PLTREL".to_string(),
+            DT_DEBUG => "DEBUG".to_string(),
+            DT_TEXTREL => "TEXTREL".to_string(),
+            DT_JMPREL => "JMPREL".to_string(),
+            DT_BIND_NOW => "BIND_NOW".to_string(),
+            DT_INIT_ARRAY => "INIT_ARRAY".to_string(),
+            DT_FINI_ARRAY => "FINI_ARRAY".to_string(),
+            DT_INIT_ARRAYSZ => "INIT_ARRAYSZ".to_string(),
+            DT_FINI_ARRAYSZ => "FINI_ARRAYSZ".to_string(),
+            DT_RUNPATH => "RUNPATH".to_string(),
+            DT_FLAGS => "FLAGS".to_string(),
+            DT_PREINIT_ARRAY => "PREINIT_ARRAY".to_string(),
+            DT_PREINIT_ARRAYSZ => "PREINIT_ARRAYSZ".to_string(),
+            DT_SYMTAB_SHNDX => "SYMTAB_SHNDX".to_string(),
+            DT_RELRSZ => "RELRSZ".to_string(),
+            DT_RELR => "RELR".to_string(),
+            DT_RELRENT => "RELRENT".to_string(),
+            DT_GNU_PRELINKED => "GNU_PRELINKED".to_string(),
+            DT_GNU_CONFLICTSZ => "GNU_CONFLICTSZ".to_string(),
+            DT_GNU_LIBLISTSZ => "GNU_LIBLISTSZ".to_string(),
+            DT_CHECKSUM => "CHECKSUM".to_string(),
+            DT_PLTPADSZ => "PLTPADSZ".to_string(),
+            DT_MOVEENT => "MOVEENT".to_string(),
+            DT_MOVESZ => "MOVESZ".to_string(),
+            DT_FEATURE => "FEATURE_1".to_string(),
+            DT_POSFLAG_1 => "POSFLAG_1".to_string(),
+            DT_SYMINSZ => "SYMINSZ".to_string(),
+            DT_SYMINENT => "SYMINENT".to_string(),
+            DT_GNU_HASH => "GNU_HASH".to_string(),
+            DT_TLSDESC_PLT => "TLSDESC_PLT".to_string(),
+            DT_TLSDESC_GOT => "TLSDESC_GOT".to_string(),
+            DT_GNU_CONFLICT => "GNU_CONFLICT".to_string(),
+            DT_GNU_LIBLIST => "GNU_LIBLIST".to_string(),
+            DT_CONFIG => "CONFIG".to_string(),
+            DT_DEPAUDIT => "DEPAUDIT".to_string(),
+            DT_AUDIT => "AUDIT".to_string(),
+            DT_PLTPAD => "PLTPAD".to_string(),
+            DT_MOVETAB => "MOVETAB".to_string(),
+            DT_SYMINFO => "SYMINFO".to_string(),
+            DT_VERSYM => "VERSYM".to_string(),
+            DT_RELACOUNT => "RELACOUNT".to_string(),
+            DT_RELCOUNT => "RELCOUNT".to_string(),
+            DT_FLAGS_1 => "FLAGS_1".to_string(),
+            DT_VERDEF => "VERDEF".to_string(),
+            DT_VERDEFNUM => "VERDEFNUM".to_string(),
+            DT_VERNEED => "VERNEED".to_string(),
+            DT_VERNEEDNUM => "VERNEEDNUM".to_string(),
+            DT_AUXILIARY => "AUXILIARY".to_string(),
+            DT_FILTER => "FILTER".to_string(),
+            _ => format!("<unknown>: {:x}", tag),
+        }
+    }
+}
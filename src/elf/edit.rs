@@ -0,0 +1,531 @@
+use crate::elf::dynamic::{
+    ElfDynamic, DT_NEEDED, DT_NULL, DT_RPATH, DT_RUNPATH, DT_SONAME, DT_STRSZ, DT_STRTAB,
+};
+use crate::elf::error::{ElfError, ElfTable};
+use crate::elf::program_header::{PF_R, PF_W, PT_DYNAMIC, PT_INTERP, PT_LOAD, PT_PHDR};
+use crate::elf::writer::{ElfWriter, ElfWriterSegment};
+use crate::elf::*;
+
+/// A change to the dynamic linking information of an executable or shared
+/// object, in the spirit of patchelf.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ElfEdit {
+    SetInterpreter(String),
+    SetRpath(String),
+    RemoveRpath,
+    SetRunpath(String),
+    RemoveRunpath,
+    SetSoname(String),
+    /// Adds a `DT_NEEDED` entry after the existing ones, unless the library
+    /// is already needed.
+    AddNeeded(String),
+    RemoveNeeded(String),
+    ReplaceNeeded(String, String),
+}
+
+impl ElfEdit {
+    fn is_dynamic(&self) -> bool {
+        !matches!(self, ElfEdit::SetInterpreter(_))
+    }
+}
+
+/// The file offset hint that sorts the relocated data after everything
+/// else; see [`ElfWriterSection::header`](crate::elf::writer::ElfWriterSection).
+const RELOCATED_OFFSET: u64 = u64::MAX / 2;
+
+/// Returns the offset of `name` in a string table, appending it when the
+/// table does not hold it yet.
+fn add_string(strtab: &mut Vec<u8>, name: &str) -> u64 {
+    let mut wanted = name.as_bytes().to_vec();
+    wanted.push(0);
+    if let Some(offset) = strtab.windows(wanted.len()).position(|w| w == wanted) {
+        return offset as u64;
+    }
+    let offset = strtab.len() as u64;
+    strtab.extend_from_slice(&wanted);
+    offset
+}
+
+fn get_string(strtab: &[u8], offset: u64) -> &[u8] {
+    let bytes = strtab.get(offset as usize..).unwrap_or_default();
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    &bytes[..len]
+}
+
+/// Overwrites the string at `offset` with `name` when it fits and none of
+/// `users` points into it, so that the table keeps its size. Leftover bytes
+/// are cleared.
+fn overwrite_string(strtab: &mut [u8], offset: u64, name: &str, users: &[u64]) -> bool {
+    let start = offset as usize;
+    let len = match strtab
+        .get(start..)
+        .and_then(|bytes| bytes.iter().position(|&b| b == 0))
+    {
+        Some(len) => len,
+        None => return false,
+    };
+    // The tail of a longer string shares its bytes with it.
+    let shared = start > 0 && strtab[start - 1] != 0;
+    let end = offset + len as u64;
+    if shared || name.len() > len || users.iter().any(|&u| offset <= u && u < end) {
+        return false;
+    }
+    let bytes = &mut strtab[start..start + len];
+    bytes.fill(0);
+    bytes[..name.len()].copy_from_slice(name.as_bytes());
+    true
+}
+
+/// Points entry `position` at `name`, reusing the bytes of its current
+/// string when nothing but `users` and the other entries could see them.
+fn set_string(
+    strtab: &mut Vec<u8>,
+    entries: &mut [ElfDynamic],
+    position: usize,
+    name: &str,
+    users: &[u64],
+) {
+    let mut others = users.to_vec();
+    others.extend(
+        entries
+            .iter()
+            .enumerate()
+            .filter(|&(i, d)| i != position && d.has_string_value())
+            .map(|(_, d)| d.value),
+    );
+    if !overwrite_string(strtab, entries[position].value, name, &others) {
+        entries[position].value = add_string(strtab, name);
+    }
+}
+
+/// Points the first entry with `tag` at `name`, or adds one after the
+/// `DT_NEEDED` entries.
+fn set_entry(
+    strtab: &mut Vec<u8>,
+    entries: &mut Vec<ElfDynamic>,
+    tag: i64,
+    name: &str,
+    users: &[u64],
+) {
+    match entries.iter().position(|d| d.tag == tag) {
+        Some(position) => set_string(strtab, entries, position, name, users),
+        None => {
+            let value = add_string(strtab, name);
+            let position = entries
+                .iter()
+                .rposition(|d| d.tag == DT_NEEDED)
+                .map_or(0, |i| i + 1);
+            entries.insert(position, ElfDynamic { tag, value });
+        }
+    }
+}
+
+/// The uses of a string table besides the dynamic entries.
+struct ElfStringUsers {
+    /// The offsets of symbol and version names, which edits leave alone.
+    names: Vec<u64>,
+    /// The `vn_file` fields naming needed libraries, as the index of their
+    /// section, the offset of the field in it and the string offset it holds.
+    verneed_files: Vec<(usize, usize, u64)>,
+}
+
+/// Returns whether section `index` can grow to `size` bytes without moving:
+/// the space after it must still lie in the file contents of its loadable
+/// segment and must not hold anything else. Addresses that overflow are
+/// reported as `None`, which callers treat as no room.
+fn has_room(writer: &ElfWriter, index: usize, size: u64) -> Option<bool> {
+    let sh = &writer.sections[index].header;
+    let (start, end) = (sh.addr, sh.addr.checked_add(size)?);
+    let load = match writer
+        .segments
+        .iter()
+        .find(|seg| seg.header.segment_type == PT_LOAD && seg.sections.contains(&index))
+    {
+        Some(load) => load,
+        None => return Some(false),
+    };
+    if end
+        > load
+            .header
+            .virtual_addr
+            .checked_add(load.header.file_size)?
+    {
+        return Some(false);
+    }
+    if load.program_headers {
+        let phdr_start = writer
+            .header
+            .program_header_offset
+            .checked_sub(load.header.offset)
+            .and_then(|delta| load.header.virtual_addr.checked_add(delta))?;
+        let phdr_end = (writer.header.program_header_size as u64)
+            .checked_mul(writer.segments.len() as u64)
+            .and_then(|size| phdr_start.checked_add(size))?;
+        if phdr_start < end && start < phdr_end {
+            return Some(false);
+        }
+    }
+    load.sections.iter().try_fold(true, |room, &i| {
+        let other = &writer.sections[i].header;
+        let other_end = other.addr.checked_add(other.size)?;
+        Some(room && (i == index || other_end <= start || end <= other.addr))
+    })
+}
+
+impl ElfFile<'_> {
+    /// Applies `edits` in order and returns the bytes of the edited file.
+    ///
+    /// A new string overwrites the one it replaces when it fits and nothing
+    /// else uses that one; otherwise it is appended to `.dynstr`, so that
+    /// symbol and version names keep their offsets. A `.dynstr`, `.dynamic` or `.interp` section that
+    /// outgrows its place is moved into a new `PT_LOAD` segment at the end
+    /// of the address space, along with the program header table, which
+    /// gains an entry for it.
+    pub fn edit(&self, edits: &[ElfEdit]) -> Result<Vec<u8>, ElfError> {
+        let mut writer = self.to_writer();
+        let mut moved = Vec::new();
+
+        if let Some(path) = edits.iter().rev().find_map(|edit| match edit {
+            ElfEdit::SetInterpreter(path) => Some(path),
+            _ => None,
+        }) {
+            let index = writer
+                .segments
+                .iter()
+                .find(|seg| seg.header.segment_type == PT_INTERP)
+                .and_then(|seg| seg.sections.first().copied())
+                .ok_or(ElfError::NoInterpreter)?;
+            let mut data = path.as_bytes().to_vec();
+            data.push(0);
+            let size = writer.sections[index].data.len();
+            if data.len() <= size {
+                data.resize(size, 0);
+            } else if !has_room(&writer, index, data.len() as u64).unwrap_or(false) {
+                moved.push(index);
+            }
+            writer.sections[index].data = data;
+        }
+
+        if edits.iter().any(ElfEdit::is_dynamic) {
+            let dynamic_index = self
+                .dynamic_table
+                .as_ref()
+                .and_then(|dt| dt.index)
+                .ok_or(ElfError::NotDynamic)?;
+            let strtab_index = writer.sections[dynamic_index].header.link as usize;
+            if strtab_index == 0 || strtab_index >= writer.sections.len() {
+                return Err(ElfError::NotDynamic);
+            }
+
+            let mut strtab = writer.sections[strtab_index].data.clone();
+            let mut entries = self.dynamic_entries();
+            let string_users = self.string_users(strtab_index);
+            let users: Vec<u64> = string_users
+                .names
+                .iter()
+                .copied()
+                .chain(string_users.verneed_files.iter().map(|&(_, _, file)| file))
+                .collect();
+            let mut renamed = Vec::new();
+            for edit in edits {
+                match edit {
+                    ElfEdit::SetInterpreter(_) => {}
+                    ElfEdit::SetRpath(path) => {
+                        set_entry(&mut strtab, &mut entries, DT_RPATH, path, &users)
+                    }
+                    ElfEdit::RemoveRpath => entries.retain(|d| d.tag != DT_RPATH),
+                    ElfEdit::SetRunpath(path) => {
+                        set_entry(&mut strtab, &mut entries, DT_RUNPATH, path, &users)
+                    }
+                    ElfEdit::RemoveRunpath => entries.retain(|d| d.tag != DT_RUNPATH),
+                    ElfEdit::SetSoname(name) => {
+                        set_entry(&mut strtab, &mut entries, DT_SONAME, name, &users)
+                    }
+                    ElfEdit::AddNeeded(name) => {
+                        let needed = entries.iter().any(|d| {
+                            d.tag == DT_NEEDED && get_string(&strtab, d.value) == name.as_bytes()
+                        });
+                        if !needed {
+                            let value = add_string(&mut strtab, name);
+                            let position = entries
+                                .iter()
+                                .rposition(|d| d.tag == DT_NEEDED)
+                                .map_or(0, |i| i + 1);
+                            let tag = DT_NEEDED;
+                            entries.insert(position, ElfDynamic { tag, value });
+                        }
+                    }
+                    ElfEdit::RemoveNeeded(name) | ElfEdit::ReplaceNeeded(name, _) => {
+                        let position = entries
+                            .iter()
+                            .position(|d| {
+                                d.tag == DT_NEEDED
+                                    && get_string(&strtab, d.value) == name.as_bytes()
+                            })
+                            .ok_or_else(|| ElfError::NoSuchLibrary(name.clone()))?;
+                        match edit {
+                            ElfEdit::ReplaceNeeded(_, new) => {
+                                // The version requirements of the library are
+                                // renamed along with it.
+                                let old = entries[position].value;
+                                let users: Vec<u64> = string_users
+                                    .names
+                                    .iter()
+                                    .copied()
+                                    .chain(
+                                        string_users
+                                            .verneed_files
+                                            .iter()
+                                            .map(|&(_, _, file)| file)
+                                            .filter(|&file| file != old),
+                                    )
+                                    .collect();
+                                set_string(&mut strtab, &mut entries, position, new, &users);
+                                renamed.push((old, entries[position].value));
+                            }
+                            _ => {
+                                entries.remove(position);
+                            }
+                        }
+                    }
+                }
+            }
+
+            for &(index, field, file) in &string_users.verneed_files {
+                let new_file = renamed.iter().fold(
+                    file,
+                    |file, &(old, new)| if file == old { new } else { file },
+                );
+                if new_file != file {
+                    let mut data = std::mem::take(&mut writer.sections[index].data);
+                    writer.cursor(&mut data, field as u64).u32(new_file as u32);
+                    writer.sections[index].data = data;
+                }
+            }
+
+            let strtab_size = writer.sections[strtab_index].data.len();
+            if strtab.len() > strtab_size
+                && !has_room(&writer, strtab_index, strtab.len() as u64).unwrap_or(false)
+            {
+                moved.push(strtab_index);
+            }
+            writer.sections[strtab_index].data = strtab;
+
+            // Unused slots stay at the end as DT_NULL entries.
+            let entry_size = if self.is_32bit() { 8 } else { 16 };
+            let slots = writer.sections[dynamic_index].data.len() / entry_size;
+            let needed_slots = entries.len() + 1;
+            entries.resize(
+                slots.max(needed_slots),
+                ElfDynamic {
+                    tag: DT_NULL,
+                    value: 0,
+                },
+            );
+            if needed_slots > slots
+                && !has_room(&writer, dynamic_index, (needed_slots * entry_size) as u64)
+                    .unwrap_or(false)
+            {
+                moved.push(dynamic_index);
+            }
+
+            if !moved.is_empty() {
+                self.relocate_sections(&mut writer, &moved)?;
+            }
+            let strtab = &writer.sections[strtab_index];
+            let (strtab_addr, strtab_size) = (strtab.header.addr, strtab.data.len() as u64);
+            for d in entries.iter_mut() {
+                match d.tag {
+                    DT_STRTAB => d.value = strtab_addr,
+                    DT_STRSZ => d.value = strtab_size,
+                    _ => {}
+                }
+            }
+            let mut data = vec![0; entries.len() * entry_size];
+            for (i, d) in entries.iter().enumerate() {
+                d.write(&mut writer.cursor(&mut data, (i * entry_size) as u64));
+            }
+            writer.sections[dynamic_index].data = data;
+        } else if !moved.is_empty() {
+            self.relocate_sections(&mut writer, &moved)?;
+        }
+
+        writer.write()
+    }
+
+    /// Collects the symbol and version names that use the string table in
+    /// section `strtab_index`.
+    fn string_users(&self, strtab_index: usize) -> ElfStringUsers {
+        let mut names = Vec::new();
+        for st in &self.symbol_tables {
+            if self
+                .section_headers
+                .get(st.index)
+                .is_some_and(|sh| sh.link as usize == strtab_index)
+            {
+                names.extend(st.symbols.iter().map(|symbol| symbol.name as u64));
+            }
+        }
+        let mut verneed_files = Vec::new();
+        for (index, sh) in self.section_headers.iter().enumerate() {
+            if sh.link as usize != strtab_index {
+                continue;
+            }
+            match sh.section_type {
+                section_header::SHT_GNU_VERDEF => {
+                    for verdef in self.read_verdefs(&sh) {
+                        names.extend(verdef.name_offsets.iter().map(|&name| name as u64));
+                    }
+                }
+                section_header::SHT_GNU_VERNEED => {
+                    for verneed in self.read_verneeds(&sh) {
+                        names.extend(verneed.entries.iter().map(|v| v.name_offset as u64));
+                        // vn_file follows vn_version and vn_cnt.
+                        let field = verneed.offset + 4;
+                        verneed_files.push((index, field, verneed.file_offset as u64));
+                    }
+                }
+                _ => {}
+            }
+        }
+        ElfStringUsers {
+            names,
+            verneed_files,
+        }
+    }
+
+    /// Moves the `moved` sections and the program header table into new
+    /// loadable segments after the existing ones: one read-only segment for
+    /// the program headers and the string tables, and a writable one for
+    /// `.dynamic`, which the dynamic linker updates at run time.
+    fn relocate_sections(&self, writer: &mut ElfWriter, moved: &[usize]) -> Result<(), ElfError> {
+        let last_load = writer
+            .segments
+            .iter()
+            .rposition(|seg| seg.header.segment_type == PT_LOAD)
+            .ok_or(ElfError::NoLoadSegment)?;
+        let first_load = writer
+            .segments
+            .iter()
+            .position(|seg| seg.header.segment_type == PT_LOAD)
+            .unwrap_or(last_load);
+        let align = writer
+            .segments
+            .iter()
+            .filter(|seg| seg.header.segment_type == PT_LOAD)
+            .map(|seg| seg.header.alignment)
+            .max()
+            .unwrap_or(1)
+            .max(1);
+        let mut addr = 0u64;
+        for (i, seg) in writer.segments.iter().enumerate() {
+            if seg.header.segment_type == PT_LOAD {
+                let end = seg
+                    .header
+                    .virtual_addr
+                    .checked_add(seg.header.memory_size)
+                    .ok_or(ElfError::LayoutOverflow {
+                        table: ElfTable::ProgramHeaders,
+                        index: i,
+                    })?;
+                addr = addr.max(end);
+            }
+        }
+        // The writer maps the program headers with the bias of the first
+        // loadable segment, so the new segment lies at its address minus that
+        // bias in the file. Leave room below it for the rest of the file,
+        // whose section name table is rebuilt and may grow.
+        let first = &writer.segments[first_load].header;
+        let bias = first.virtual_addr.wrapping_sub(first.offset);
+        let slack = writer.sections.iter().fold(0u64, |slack, section| {
+            slack
+                .saturating_add(section.name.len() as u64 + 1)
+                .saturating_add(section.header.alignment)
+        });
+        let file_end = (self.data.len() as u64)
+            .checked_add(slack)
+            .and_then(|end| end.checked_add(bias))
+            .ok_or(ElfError::LayoutOverflow {
+                table: ElfTable::ProgramHeaders,
+                index: first_load,
+            })?;
+        addr = addr.max(file_end);
+
+        let (readonly, writable): (Vec<usize>, Vec<usize>) = moved
+            .iter()
+            .partition(|&&i| writer.sections[i].header.flags & section_header::SHF_WRITE == 0);
+        let new_count = if writable.is_empty() { 1 } else { 2 };
+        let mut new_segments = Vec::new();
+        let mut hint = RELOCATED_OFFSET;
+        for (flags, sections) in [(PF_R, readonly), (PF_R | PF_W, writable)] {
+            let program_headers = flags == PF_R;
+            if sections.is_empty() && !program_headers {
+                continue;
+            }
+            let overflow = || ElfError::LayoutOverflow {
+                table: ElfTable::ProgramHeaders,
+                index: last_load + 1 + new_segments.len(),
+            };
+            addr = addr.checked_next_multiple_of(align).ok_or_else(overflow)?;
+            let mut header = writer.segments[last_load].header;
+            header.flags = flags;
+            header.offset = hint;
+            header.virtual_addr = addr;
+            header.physical_addr = addr;
+            header.file_size = 0;
+            header.memory_size = 0;
+            header.alignment = align;
+            if program_headers {
+                let count = writer.segments.len() + new_count;
+                writer.header.program_header_offset = hint;
+                addr = (writer.header.program_header_size as u64)
+                    .checked_mul(count as u64)
+                    .and_then(|size| addr.checked_add(size))
+                    .ok_or_else(overflow)?;
+                for seg in &mut writer.segments {
+                    if seg.header.segment_type == PT_PHDR {
+                        seg.header.virtual_addr = header.virtual_addr;
+                        seg.header.physical_addr = header.virtual_addr;
+                    }
+                    seg.program_headers &= seg.header.segment_type == PT_PHDR;
+                }
+            }
+            for &i in &sections {
+                hint += 1;
+                let overflow = || ElfError::LayoutOverflow {
+                    table: ElfTable::SectionData,
+                    index: i,
+                };
+                let section = &mut writer.sections[i];
+                addr = addr
+                    .checked_next_multiple_of(section.header.alignment.max(1))
+                    .ok_or_else(overflow)?;
+                section.header.addr = addr;
+                section.header.offset = hint;
+                addr = addr
+                    .checked_add(section.data.len() as u64)
+                    .ok_or_else(overflow)?;
+                for seg in &mut writer.segments {
+                    match seg.header.segment_type {
+                        PT_DYNAMIC | PT_INTERP if seg.sections.contains(&i) => {
+                            seg.header.virtual_addr = writer.sections[i].header.addr;
+                            seg.header.physical_addr = writer.sections[i].header.addr;
+                        }
+                        _ => seg.sections.retain(|&s| s != i),
+                    }
+                }
+            }
+            hint += 1;
+            new_segments.push(ElfWriterSegment {
+                header,
+                sections,
+                program_headers,
+                data: Vec::new(),
+            });
+        }
+
+        let at = last_load + 1;
+        writer.segments.splice(at..at, new_segments);
+        Ok(())
+    }
+}
@@ -1,6 +1,16 @@
+use crate::elf::dynamic::{DF_1_PIE, DT_FLAGS_1};
+use crate::elf::elf_machine::ElfMachine;
+use crate::elf::elf_symbol::SHN_XINDEX;
+use crate::elf::error::{ElfError, ElfTable};
+use crate::elf::header_flags::get_header_flag_names;
+use crate::elf::program_header::{ELF32_PROGRAM_HEADER_SIZE, ELF64_PROGRAM_HEADER_SIZE, PN_XNUM};
+use crate::elf::reader::ElfReader;
+use crate::elf::section_header::{ELF32_SECTION_HEADER_SIZE, ELF64_SECTION_HEADER_SIZE};
+use crate::elf::writer::ElfWriteCursor;
 use crate::elf::*;
 
-#[repr(C)]
+use std::fmt;
+
 #[derive(Copy, Clone, Debug)]
 pub struct ElfHeader {
     pub ident: ElfIdent,
@@ -19,6 +29,8 @@ pub struct ElfHeader {
     pub string_table_index: ElfHalf,
 }
 
+pub const ELFMAG: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+
 pub const EI_NIDENT: usize = 16;
 pub const EI_CLASS: usize = 4;
 pub const EI_DATA: usize = 5;
@@ -28,6 +40,9 @@ pub const EI_ABIVERSION: usize = 8;
 
 pub const EV_CURRENT: u8 = 1;
 
+pub const ELF32_HEADER_SIZE: usize = 52;
+pub const ELF64_HEADER_SIZE: usize = 64;
+
 pub const ELF_CLASS_NONE: u8 = 0;
 pub const ELF_CLASS_32: u8 = 1;
 pub const ELF_CLASS_64: u8 = 2;
@@ -37,26 +52,307 @@ pub const ELF_DATA_2_LSB: u8 = 1;
 pub const ELF_DATA_2_MSB: u8 = 2;
 
 pub const ELF_OSABI_NONE: u8 = 0;
+pub const ELF_OSABI_HPUX: u8 = 1;
+pub const ELF_OSABI_NETBSD: u8 = 2;
+pub const ELF_OSABI_GNU: u8 = 3;
+pub const ELF_OSABI_SOLARIS: u8 = 6;
+pub const ELF_OSABI_AIX: u8 = 7;
+pub const ELF_OSABI_IRIX: u8 = 8;
+pub const ELF_OSABI_FREEBSD: u8 = 9;
+pub const ELF_OSABI_TRU64: u8 = 10;
+pub const ELF_OSABI_MODESTO: u8 = 11;
+pub const ELF_OSABI_OPENBSD: u8 = 12;
+pub const ELF_OSABI_OPENVMS: u8 = 13;
+pub const ELF_OSABI_NSK: u8 = 14;
+pub const ELF_OSABI_AROS: u8 = 15;
+pub const ELF_OSABI_FENIXOS: u8 = 16;
+pub const ELF_OSABI_CLOUDABI: u8 = 17;
+pub const ELF_OSABI_OPENVOS: u8 = 18;
+pub const ELF_OSABI_ARM_FDPIC: u8 = 65;
+pub const ELF_OSABI_ARM: u8 = 97;
+pub const ELF_OSABI_STANDALONE: u8 = 255;
 
 pub const ET_NONE: u16 = 0;
 pub const ET_REL: u16 = 1;
 pub const ET_EXEC: u16 = 2;
 pub const ET_DYN: u16 = 3;
 pub const ET_CORE: u16 = 4;
+pub const ET_LOOS: u16 = 0xfe00;
+pub const ET_HIOS: u16 = 0xfeff;
+pub const ET_LOPROC: u16 = 0xff00;
+pub const ET_HIPROC: u16 = 0xffff;
+
+/// The `EI_OSABI` byte of the ELF identification. Values from 64 up are
+/// defined per architecture.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ElfOsAbi {
+    SystemV,
+    HpUx,
+    NetBsd,
+    Gnu,
+    Solaris,
+    Aix,
+    Irix,
+    FreeBsd,
+    Tru64,
+    Modesto,
+    OpenBsd,
+    OpenVms,
+    Nsk,
+    Aros,
+    FenixOs,
+    CloudAbi,
+    OpenVos,
+    ArmFdpic,
+    Arm,
+    Standalone,
+    ArchSpecific(u8),
+    Unknown(u8),
+}
+
+/// The `e_type` field of the ELF header.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ElfFileType {
+    None,
+    Relocatable,
+    Executable,
+    SharedObject,
+    Core,
+    OsSpecific(u16),
+    ProcessorSpecific(u16),
+    Unknown(u16),
+}
+
+impl From<u8> for ElfOsAbi {
+    fn from(osabi: u8) -> Self {
+        match osabi {
+            ELF_OSABI_NONE => ElfOsAbi::SystemV,
+            ELF_OSABI_HPUX => ElfOsAbi::HpUx,
+            ELF_OSABI_NETBSD => ElfOsAbi::NetBsd,
+            ELF_OSABI_GNU => ElfOsAbi::Gnu,
+            ELF_OSABI_SOLARIS => ElfOsAbi::Solaris,
+            ELF_OSABI_AIX => ElfOsAbi::Aix,
+            ELF_OSABI_IRIX => ElfOsAbi::Irix,
+            ELF_OSABI_FREEBSD => ElfOsAbi::FreeBsd,
+            ELF_OSABI_TRU64 => ElfOsAbi::Tru64,
+            ELF_OSABI_MODESTO => ElfOsAbi::Modesto,
+            ELF_OSABI_OPENBSD => ElfOsAbi::OpenBsd,
+            ELF_OSABI_OPENVMS => ElfOsAbi::OpenVms,
+            ELF_OSABI_NSK => ElfOsAbi::Nsk,
+            ELF_OSABI_AROS => ElfOsAbi::Aros,
+            ELF_OSABI_FENIXOS => ElfOsAbi::FenixOs,
+            ELF_OSABI_CLOUDABI => ElfOsAbi::CloudAbi,
+            ELF_OSABI_OPENVOS => ElfOsAbi::OpenVos,
+            ELF_OSABI_STANDALONE => ElfOsAbi::Standalone,
+            64..=254 => ElfOsAbi::ArchSpecific(osabi),
+            _ => ElfOsAbi::Unknown(osabi),
+        }
+    }
+}
+
+impl ElfOsAbi {
+    /// Decodes `EI_OSABI`, resolving the architecture-specific values that
+    /// are known for `machine`.
+    pub fn for_machine(osabi: u8, machine: ElfMachine) -> Self {
+        match (osabi, machine) {
+            (ELF_OSABI_ARM_FDPIC, ElfMachine::Arm) => ElfOsAbi::ArmFdpic,
+            (ELF_OSABI_ARM, ElfMachine::Arm) => ElfOsAbi::Arm,
+            _ => ElfOsAbi::from(osabi),
+        }
+    }
+}
+
+impl fmt::Display for ElfOsAbi {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ElfOsAbi::SystemV => write!(f, "UNIX - System V"),
+            ElfOsAbi::HpUx => write!(f, "UNIX - HP-UX"),
+            ElfOsAbi::NetBsd => write!(f, "UNIX - NetBSD"),
+            ElfOsAbi::Gnu => write!(f, "UNIX - GNU"),
+            ElfOsAbi::Solaris => write!(f, "UNIX - Solaris"),
+            ElfOsAbi::Aix => write!(f, "UNIX - AIX"),
+            ElfOsAbi::Irix => write!(f, "UNIX - IRIX"),
+            ElfOsAbi::FreeBsd => write!(f, "UNIX - FreeBSD"),
+            ElfOsAbi::Tru64 => write!(f, "UNIX - TRU64"),
+            ElfOsAbi::Modesto => write!(f, "Novell - Modesto"),
+            ElfOsAbi::OpenBsd => write!(f, "UNIX - OpenBSD"),
+            ElfOsAbi::OpenVms => write!(f, "VMS - OpenVMS"),
+            ElfOsAbi::Nsk => write!(f, "HP - Non-Stop Kernel"),
+            ElfOsAbi::Aros => write!(f, "AROS"),
+            ElfOsAbi::FenixOs => write!(f, "FenixOS"),
+            ElfOsAbi::CloudAbi => write!(f, "Nuxi CloudABI"),
+            ElfOsAbi::OpenVos => write!(f, "Stratus Technologies OpenVOS"),
+            ElfOsAbi::ArmFdpic => write!(f, "ARM FDPIC"),
+            ElfOsAbi::Arm => write!(f, "ARM"),
+            ElfOsAbi::Standalone => write!(f, "Standalone App"),
+            ElfOsAbi::ArchSpecific(osabi) => write!(f, "<architecture specific>: 0x{:x}", osabi),
+            ElfOsAbi::Unknown(osabi) => write!(f, "<unknown>: 0x{:x}", osabi),
+        }
+    }
+}
+
+impl From<u16> for ElfFileType {
+    fn from(filetype: u16) -> Self {
+        match filetype {
+            ET_NONE => ElfFileType::None,
+            ET_REL => ElfFileType::Relocatable,
+            ET_EXEC => ElfFileType::Executable,
+            ET_DYN => ElfFileType::SharedObject,
+            ET_CORE => ElfFileType::Core,
+            ET_LOOS..=ET_HIOS => ElfFileType::OsSpecific(filetype),
+            ET_LOPROC..=ET_HIPROC => ElfFileType::ProcessorSpecific(filetype),
+            _ => ElfFileType::Unknown(filetype),
+        }
+    }
+}
+
+impl fmt::Display for ElfFileType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ElfFileType::None => write!(f, "NONE (None)"),
+            ElfFileType::Relocatable => write!(f, "REL (Relocatable file)"),
+            ElfFileType::Executable => write!(f, "EXEC (Executable file)"),
+            ElfFileType::SharedObject => write!(f, "DYN (Shared object file)"),
+            ElfFileType::Core => write!(f, "CORE (Core file)"),
+            ElfFileType::OsSpecific(filetype) => write!(f, "OS Specific: (0x{:x})", filetype),
+            ElfFileType::ProcessorSpecific(filetype) => {
+                write!(f, "Processor Specific: (0x{:x})", filetype)
+            }
+            ElfFileType::Unknown(filetype) => write!(f, "<unknown>: 0x{:x}", filetype),
+        }
+    }
+}
+
+impl ElfHeader {
+    pub fn machine_type(&self) -> ElfMachine {
+        ElfMachine::from(self.machine)
+    }
+
+    pub fn osabi(&self) -> ElfOsAbi {
+        ElfOsAbi::for_machine(self.ident[EI_OSABI], self.machine_type())
+    }
+
+    pub fn file_type(&self) -> ElfFileType {
+        ElfFileType::from(self.filetype)
+    }
 
-pub const EM_NONE: u16 = 0;
-pub const EM_X86_64: u16 = 62;
+    /// Returns the decoded names of the processor-specific `e_flags`.
+    pub fn flag_names(&self) -> Vec<String> {
+        get_header_flag_names(self.machine_type(), self.flags)
+    }
+
+    /// Decodes and validates the ELF header at the start of the file.
+    pub(crate) fn parse(reader: &ElfReader) -> Result<Self, ElfError> {
+        let ident = reader.bytes(0, EI_NIDENT).ok_or(ElfError::BadMagic)?;
+        Self::validate_ident(ident)?;
+
+        let (size, phentsize, shentsize) = if reader.is_32bit() {
+            (
+                ELF32_HEADER_SIZE,
+                ELF32_PROGRAM_HEADER_SIZE,
+                ELF32_SECTION_HEADER_SIZE,
+            )
+        } else {
+            (
+                ELF64_HEADER_SIZE,
+                ELF64_PROGRAM_HEADER_SIZE,
+                ELF64_SECTION_HEADER_SIZE,
+            )
+        };
+        let header = Self::decode(reader, size).ok_or(ElfError::OutOfRange {
+            table: ElfTable::Header,
+            index: 0,
+            offset: 0,
+            size: size as u64,
+        })?;
+
+        Self::validate_size("e_ehsize", header.elf_header_size, size)?;
+        if header.program_header_num != 0 {
+            Self::validate_size("e_phentsize", header.program_header_size, phentsize)?;
+        }
+        if header.section_header_num != 0 {
+            Self::validate_size("e_shentsize", header.section_header_size, shentsize)?;
+        }
+        Ok(header)
+    }
+
+    fn validate_ident(ident: &[u8]) -> Result<(), ElfError> {
+        if !ident.starts_with(&ELFMAG) {
+            return Err(ElfError::BadMagic);
+        }
+        match ident[EI_CLASS] {
+            ELF_CLASS_32 | ELF_CLASS_64 => {}
+            class => return Err(ElfError::UnsupportedClass(class)),
+        }
+        match ident[EI_DATA] {
+            ELF_DATA_2_LSB | ELF_DATA_2_MSB => {}
+            encoding => return Err(ElfError::UnsupportedEncoding(encoding)),
+        }
+        if ident[EI_VERSION] != EV_CURRENT {
+            return Err(ElfError::UnsupportedVersion(ident[EI_VERSION]));
+        }
+        Ok(())
+    }
+
+    fn validate_size(field: &'static str, size: u16, expected: usize) -> Result<(), ElfError> {
+        if size as usize != expected {
+            return Err(ElfError::BadHeaderSize {
+                field,
+                size,
+                expected: expected as u16,
+            });
+        }
+        Ok(())
+    }
+
+    pub(crate) fn write(&self, c: &mut ElfWriteCursor) {
+        c.ident(self.ident);
+        c.u16(self.filetype);
+        c.u16(self.machine);
+        c.u32(self.version);
+        c.addr(self.entrypoint);
+        c.addr(self.program_header_offset);
+        c.addr(self.section_header_offset);
+        c.u32(self.flags);
+        c.u16(self.elf_header_size);
+        c.u16(self.program_header_size);
+        c.u16(self.program_header_num);
+        c.u16(self.section_header_size);
+        c.u16(self.section_header_num);
+        c.u16(self.string_table_index);
+    }
 
-impl ElfFile {
+    fn decode(reader: &ElfReader, size: usize) -> Option<Self> {
+        let mut c = reader.cursor(0, size)?;
+        Some(Self {
+            ident: c.ident()?,
+            filetype: c.u16()?,
+            machine: c.u16()?,
+            version: c.u32()?,
+            entrypoint: c.addr()?,
+            program_header_offset: c.addr()?,
+            section_header_offset: c.addr()?,
+            flags: c.u32()?,
+            elf_header_size: c.u16()?,
+            program_header_size: c.u16()?,
+            program_header_num: c.u16()?,
+            section_header_size: c.u16()?,
+            section_header_num: c.u16()?,
+            string_table_index: c.u16()?,
+        })
+    }
+}
+
+impl ElfFile<'_> {
     pub fn show_header(&self) {
         let header = &self.header;
-        let ident_bytes: [u8; 16] = header.ident.to_le_bytes();
+        let ident_bytes = header.ident;
 
         println!("ELF Header:");
 
         print!(" Magic:");
-        for i in 0..EI_NIDENT {
-            print!(" {:02X}", ident_bytes[i]);
+        for byte in ident_bytes.iter().take(EI_NIDENT) {
+            print!(" {:02X}", byte);
         }
         println!();
 
@@ -71,10 +367,10 @@ impl ElfFile {
                 "<unknown>"
             }
         );
-        println!(" OS/ABI: {}", self.get_osabi_name(ident_bytes[EI_OSABI]));
+        println!(" OS/ABI: {}", header.osabi());
         println!(" ABI Version: {}", ident_bytes[EI_ABIVERSION]);
-        println!(" Type: {}", self.get_filetype(header.filetype));
-        println!(" Machine: {}", self.get_machine_name(header.machine));
+        println!(" Type: {}", self.get_filetype());
+        println!(" Machine: {}", header.machine_type());
         println!(" Version: 0x{:X}", header.version);
         println!(" Entry point address: 0x{:X}", header.entrypoint);
         println!(
@@ -85,24 +381,54 @@ impl ElfFile {
             " Start of section headers: {} (bytes into file)",
             header.section_header_offset
         );
-        println!(" Flags: 0x{:X}", header.flags);
+        let mut flags = vec![format!("0x{:X}", header.flags)];
+        flags.extend(header.flag_names());
+        println!(" Flags: {}", flags.join(", "));
         println!(" Size of this header: {} (bytes)", header.elf_header_size);
         println!(
             " Size of program headers: {} (bytes)",
             header.program_header_size
         );
-        println!(" Number of program headers: {}", header.program_header_num);
+        println!(
+            " Number of program headers: {}",
+            self.get_extended_number(
+                header.program_header_num,
+                self.program_headers.len(),
+                header.program_header_num == PN_XNUM
+            )
+        );
         println!(
             " Size of section headers: {} (bytes)",
             header.section_header_size
         );
-        println!(" Number of section headers: {}", header.section_header_num);
+        println!(
+            " Number of section headers: {}",
+            self.get_extended_number(
+                header.section_header_num,
+                self.section_headers.len(),
+                header.section_header_num == 0
+            )
+        );
         println!(
             " Section header string table index: {}",
-            header.string_table_index
+            self.get_extended_number(
+                header.string_table_index,
+                self.string_table_index,
+                header.string_table_index == SHN_XINDEX
+            )
         );
     }
 
+    /// Formats a header count or index, adding the real value taken from
+    /// section 0 when the field is an escape value.
+    fn get_extended_number(&self, value: ElfHalf, resolved: usize, extended: bool) -> String {
+        if extended && resolved != value as usize {
+            format!("{} ({})", value, resolved)
+        } else {
+            value.to_string()
+        }
+    }
+
     fn get_class_name(&self, class: u8) -> String {
         match class {
             ELF_CLASS_NONE => "none".to_string(),
@@ -121,30 +447,16 @@ impl ElfFile {
         }
     }
 
-    fn get_osabi_name(&self, osabi: u8) -> String {
-        match osabi {
-            ELF_OSABI_NONE => "UNIX - System V".to_string(),
-            // (.. snip ..)
-            _ => format!("<unknown>: {:X}", osabi),
-        }
-    }
-
-    fn get_filetype(&self, filetype: u16) -> String {
-        match filetype {
-            ET_NONE => "NONE (None)".to_string(),
-            ET_REL => "REL (Relocatable file)".to_string(),
-            ET_EXEC => "EXEC (Executable file)".to_string(),
-            ET_DYN => "DYN (hared object file)".to_string(),
-            ET_CORE => "CORE (Core file)".to_string(),
-            _ => format!("<unknown>: {:X}", filetype),
-        }
-    }
-
-    fn get_machine_name(&self, machine: u16) -> String {
-        match machine {
-            EM_NONE => "None".to_string(),
-            EM_X86_64 => "Advanced Micro Devices X86-64".to_string(),
-            _ => format!("<unknown>: {:X}", machine),
+    /// Describes `e_type`, telling position-independent executables apart
+    /// from shared objects through `DF_1_PIE`.
+    fn get_filetype(&self) -> String {
+        let filetype = self.header.file_type();
+        let is_pie = self
+            .get_dynamic_value(DT_FLAGS_1)
+            .is_some_and(|flags| flags & DF_1_PIE != 0);
+        if filetype == ElfFileType::SharedObject && is_pie {
+            return "DYN (Position-Independent Executable file)".to_string();
         }
+        filetype.to_string()
     }
 }
@@ -0,0 +1,237 @@
+use std::fmt;
+
+macro_rules! elf_machines {
+    ($($variant:ident = $value:literal, $description:literal;)*) => {
+        /// The `e_machine` field of the ELF header.
+        #[allow(non_camel_case_types)]
+        #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+        pub enum ElfMachine {
+            $($variant,)*
+            Unknown(u16),
+        }
+
+        impl From<u16> for ElfMachine {
+            fn from(machine: u16) -> Self {
+                match machine {
+                    $($value => ElfMachine::$variant,)*
+                    _ => ElfMachine::Unknown(machine),
+                }
+            }
+        }
+
+        impl From<ElfMachine> for u16 {
+            fn from(machine: ElfMachine) -> Self {
+                match machine {
+                    $(ElfMachine::$variant => $value,)*
+                    ElfMachine::Unknown(machine) => machine,
+                }
+            }
+        }
+
+        impl ElfMachine {
+            /// Returns the description GNU readelf prints for this machine.
+            pub fn description(&self) -> Option<&'static str> {
+                match self {
+                    $(ElfMachine::$variant => Some($description),)*
+                    ElfMachine::Unknown(_) => None,
+                }
+            }
+        }
+    };
+}
+
+elf_machines! {
+    None = 0, "None";
+    M32 = 1, "WE32100";
+    Sparc = 2, "Sparc";
+    I386 = 3, "Intel 80386";
+    M68k = 4, "MC68000";
+    M88k = 5, "MC88000";
+    Iamcu = 6, "Intel MCU";
+    I860 = 7, "Intel 80860";
+    Mips = 8, "MIPS R3000";
+    S370 = 9, "IBM System/370";
+    MipsRs3Le = 10, "MIPS R4000 big-endian";
+    Parisc = 15, "HPPA";
+    Vpp500 = 17, "Fujitsu VPP500";
+    Sparc32Plus = 18, "Sparc v8+";
+    I960 = 19, "Intel 80960";
+    Ppc = 20, "PowerPC";
+    Ppc64 = 21, "PowerPC64";
+    S390 = 22, "IBM S/390";
+    Spu = 23, "SPU";
+    V800 = 36, "Renesas V850 (using RH850 ABI)";
+    Fr20 = 37, "Fujitsu FR20";
+    Rh32 = 38, "TRW RH32";
+    Mcore = 39, "MCORE";
+    Arm = 40, "ARM";
+    OldAlpha = 41, "Digital Alpha (old)";
+    Sh = 42, "Renesas / SuperH SH";
+    SparcV9 = 43, "Sparc v9";
+    Tricore = 44, "Siemens Tricore";
+    Arc = 45, "ARC";
+    H8_300 = 46, "Renesas H8/300";
+    H8_300h = 47, "Renesas H8/300H";
+    H8s = 48, "Renesas H8S";
+    H8_500 = 49, "Renesas H8/500";
+    Ia64 = 50, "Intel IA-64";
+    MipsX = 51, "Stanford MIPS-X";
+    Coldfire = 52, "Motorola Coldfire";
+    M68hc12 = 53, "Motorola MC68HC12 Microcontroller";
+    Mma = 54, "Fujitsu Multimedia Accelerator";
+    Pcp = 55, "Siemens PCP";
+    Ncpu = 56, "Sony nCPU embedded RISC processor";
+    Ndr1 = 57, "Denso NDR1 microprocesspr";
+    Starcore = 58, "Motorola Star*Core processor";
+    Me16 = 59, "Toyota ME16 processor";
+    St100 = 60, "STMicroelectronics ST100 processor";
+    Tinyj = 61, "Advanced Logic Corp. TinyJ embedded processor";
+    X86_64 = 62, "Advanced Micro Devices X86-64";
+    Pdsp = 63, "Sony DSP processor";
+    Pdp10 = 64, "Digital Equipment Corp. PDP-10";
+    Pdp11 = 65, "Digital Equipment Corp. PDP-11";
+    Fx66 = 66, "Siemens FX66 microcontroller";
+    St9plus = 67, "STMicroelectronics ST9+ 8/16 bit microcontroller";
+    St7 = 68, "STMicroelectronics ST7 8-bit microcontroller";
+    M68hc16 = 69, "Motorola MC68HC16 Microcontroller";
+    M68hc11 = 70, "Motorola MC68HC11 Microcontroller";
+    M68hc08 = 71, "Motorola MC68HC08 Microcontroller";
+    M68hc05 = 72, "Motorola MC68HC05 Microcontroller";
+    Svx = 73, "Silicon Graphics SVx";
+    St19 = 74, "STMicroelectronics ST19 8-bit microcontroller";
+    Vax = 75, "Digital VAX";
+    Cris = 76, "Axis Communications 32-bit embedded processor";
+    Javelin = 77, "Infineon Technologies 32-bit embedded cpu";
+    Firepath = 78, "Element 14 64-bit DSP processor";
+    Zsp = 79, "LSI Logic's 16-bit DSP processor";
+    Mmix = 80, "Donald Knuth's educational 64-bit processor";
+    Huany = 81, "Harvard Universitys's machine-independent object format";
+    Prism = 82, "Vitesse Prism";
+    Avr = 83, "Atmel AVR 8-bit microcontroller";
+    Fr30 = 84, "Fujitsu FR30";
+    D10v = 85, "d10v";
+    D30v = 86, "d30v";
+    V850 = 87, "Renesas V850";
+    M32r = 88, "Renesas M32R (formerly Mitsubishi M32r)";
+    Mn10300 = 89, "mn10300";
+    Mn10200 = 90, "mn10200";
+    Pj = 91, "picoJava";
+    Or1k = 92, "OpenRISC 1000";
+    ArcCompact = 93, "ARCompact";
+    Xtensa = 94, "Tensilica Xtensa Processor";
+    Videocore = 95, "Alphamosaic VideoCore processor";
+    TmmGpp = 96, "Thompson Multimedia General Purpose Processor";
+    Ns32k = 97, "National Semiconductor 32000 series";
+    Tpc = 98, "Tenor Network TPC processor";
+    Snp1k = 99, "Trebia SNP 1000 processor";
+    St200 = 100, "STMicroelectronics ST200 microcontroller";
+    Ip2k = 101, "Ubicom IP2xxx 8-bit microcontrollers";
+    Max = 102, "MAX Processor";
+    Cr = 103, "National Semiconductor CompactRISC";
+    F2mc16 = 104, "Fujitsu F2MC16";
+    Msp430 = 105, "Texas Instruments msp430 microcontroller";
+    Blackfin = 106, "Analog Devices Blackfin";
+    SeC33 = 107, "S1C33 Family of Seiko Epson processors";
+    Sep = 108, "Sharp embedded microprocessor";
+    Arca = 109, "Arca RISC microprocessor";
+    Unicore = 110, "Unicore";
+    Excess = 111, "eXcess 16/32/64-bit configurable embedded CPU";
+    Dxp = 112, "Icera Semiconductor Inc. Deep Execution Processor";
+    AlteraNios2 = 113, "Altera Nios II";
+    Crx = 114, "National Semiconductor CRX microprocessor";
+    Xgate = 115, "Motorola XGATE embedded processor";
+    C166 = 116, "Infineon Technologies xc16x";
+    M16c = 117, "Renesas M16C series microprocessors";
+    Dspic30f = 118, "Microchip Technology dsPIC30F Digital Signal Controller";
+    Ce = 119, "Freescale Communication Engine RISC core";
+    M32c = 120, "Renesas M32c";
+    Tsk3000 = 131, "Altium TSK3000 core";
+    Rs08 = 132, "Freescale RS08 embedded processor";
+    Sharc = 133, "Analog Devices SHARC family of 32-bit DSP processors";
+    Ecog2 = 134, "Cyan Technology eCOG2 microprocessor";
+    Score = 135, "SUNPLUS S+Core";
+    Dsp24 = 136, "New Japan Radio (NJR) 24-bit DSP Processor";
+    Videocore3 = 137, "Broadcom VideoCore III processor";
+    Latticemico32 = 138, "Lattice Mico32";
+    SeC17 = 139, "Seiko Epson C17 family";
+    TiC6000 = 140, "Texas Instruments TMS320C6000 DSP family";
+    TiC2000 = 141, "Texas Instruments TMS320C2000 DSP family";
+    TiC5500 = 142, "Texas Instruments TMS320C55x DSP family";
+    TiArp32 = 143, "Texas Instruments Application Specific RISC Processor, 32bit fetch";
+    TiPru = 144, "Texas Instruments Programmable Realtime Unit";
+    MmdspPlus = 160, "STMicroelectronics 64bit VLIW Data Signal Processor";
+    CypressM8c = 161, "Cypress M8C microprocessor";
+    R32c = 162, "Renesas R32C series microprocessors";
+    Trimedia = 163, "NXP Semiconductors TriMedia architecture family";
+    Qdsp6 = 164, "QUALCOMM DSP6 Processor";
+    I8051 = 165, "Intel 8051 and variants";
+    Stxp7x = 166, "STMicroelectronics STxP7x family";
+    Nds32 = 167, "Andes Technology compact code size embedded RISC processor family";
+    Ecog1x = 168, "Cyan Technology eCOG1X family";
+    Maxq30 = 169, "Dallas Semiconductor MAXQ30 Core microcontrollers";
+    Ximo16 = 170, "New Japan Radio (NJR) 16-bit DSP Processor";
+    Manik = 171, "M2000 Reconfigurable RISC Microprocessor";
+    Craynv2 = 172, "Cray Inc. NV2 vector architecture";
+    Rx = 173, "Renesas RX";
+    Metag = 174, "Imagination Technologies Meta processor architecture";
+    McstElbrus = 175, "MCST Elbrus general purpose hardware architecture";
+    Ecog16 = 176, "Cyan Technology eCOG16 family";
+    Cr16 = 177, "National Semiconductor's CR16";
+    Etpu = 178, "Freescale Extended Time Processing Unit";
+    Sle9x = 179, "Infineon Technologies SLE9X core";
+    L1om = 180, "Intel L1OM";
+    K1om = 181, "Intel K1OM";
+    Aarch64 = 183, "AArch64";
+    Avr32 = 185, "Atmel Corporation 32-bit microprocessor";
+    Stm8 = 186, "STMicroeletronics STM8 8-bit microcontroller";
+    Tile64 = 187, "Tilera TILE64 multicore architecture family";
+    Tilepro = 188, "Tilera TILEPro multicore architecture family";
+    Microblaze = 189, "Xilinx MicroBlaze";
+    Cuda = 190, "NVIDIA CUDA architecture";
+    Tilegx = 191, "Tilera TILE-Gx multicore architecture family";
+    Cloudshield = 192, "CloudShield architecture family";
+    Corea1st = 193, "KIPO-KAIST Core-A 1st generation processor family";
+    Corea2nd = 194, "KIPO-KAIST Core-A 2nd generation processor family";
+    ArcCompact2 = 195, "ARCv2";
+    Open8 = 196, "Open8 8-bit RISC soft processor core";
+    Rl78 = 197, "Renesas RL78";
+    Videocore5 = 198, "Broadcom VideoCore V processor";
+    R78kor = 199, "Renesas 78K0R";
+    F56800ex = 200, "Freescale 56800EX Digital Signal Controller (DSC)";
+    Ba1 = 201, "Beyond BA1 CPU architecture";
+    Ba2 = 202, "Beyond BA2 CPU architecture";
+    Xcore = 203, "XMOS xCORE processor family";
+    MchpPic = 204, "Microchip 8-bit PIC(r) family";
+    Intelgt = 205, "Intel Graphics Technology";
+    Km32 = 210, "KM211 KM32 32-bit processor";
+    Kmx32 = 211, "KM211 KMX32 32-bit processor";
+    Kmx16 = 212, "KM211 KMX16 16-bit processor";
+    Kmx8 = 213, "KM211 KMX8 8-bit processor";
+    Kvarc = 214, "KM211 KVARC processor";
+    Cdp = 215, "Paneve CDP architecture family";
+    Coge = 216, "Cognitive Smart Memory Processor";
+    Cool = 217, "Bluechip Systems CoolEngine";
+    Norc = 218, "Nanoradio Optimized RISC";
+    CsrKalimba = 219, "CSR Kalimba architecture family";
+    Z80 = 220, "Zilog Z80";
+    Visium = 221, "CDS VISIUMcore processor";
+    Ft32 = 222, "FTDI Chip FT32";
+    Moxie = 223, "Moxie";
+    Amdgpu = 224, "AMD GPU";
+    Riscv = 243, "RISC-V";
+    Lanai = 244, "Lanai 32-bit processor";
+    Bpf = 247, "Linux BPF";
+    Csky = 252, "C-SKY";
+    Kvx = 256, "Kalray VLIW core of the MPPA processor family";
+    Loongarch = 258, "LoongArch";
+    Alpha = 0x9026, "Alpha";
+}
+
+impl fmt::Display for ElfMachine {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.description() {
+            Some(description) => write!(f, "{}", description),
+            None => write!(f, "<unknown>: 0x{:x}", u16::from(*self)),
+        }
+    }
+}
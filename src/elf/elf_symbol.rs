@@ -1,14 +1,18 @@
+use crate::elf::reader::ElfReader;
+use crate::elf::table::LazyTable;
 use crate::elf::*;
 
-use prettytable::{cell, format, row, Table};
+use prettytable::{format, row, Table};
 
 #[derive(Debug)]
-pub struct ElfSymbolTable {
+pub struct ElfSymbolTable<'data> {
     pub index: usize,
-    pub symbols: Vec<ElfSymbol>,
+    pub symbols: LazyTable<'data, ElfSymbol>,
+    /// The `SHT_SYMTAB_SHNDX` section holding the section indexes of symbols
+    /// whose `st_shndx` is `SHN_XINDEX`.
+    pub extended_section_indexes: Option<LazyTable<'data, ElfWord>>,
 }
 
-#[repr(C)]
 #[derive(Copy, Clone, Debug)]
 pub struct ElfSymbol {
     pub name: ElfWord,
@@ -26,49 +30,134 @@ pub struct ElfSymbolInfo {
     pub flags: ElfHalf,
 }
 
-const STB_LOCAL: u8 = 0;
-const STB_GLOBAL: u8 = 1;
-const STB_WEAK: u8 = 2;
+pub const ELF32_SYMBOL_SIZE: usize = 16;
+pub const ELF64_SYMBOL_SIZE: usize = 24;
+pub const ELF_SYMTAB_SHNDX_SIZE: usize = 4;
+
+pub const STB_LOCAL: u8 = 0;
+pub const STB_GLOBAL: u8 = 1;
+pub const STB_WEAK: u8 = 2;
+
+pub const STT_NOTYPE: u8 = 0;
+pub const STT_OBJECT: u8 = 1;
+pub const STT_FUNC: u8 = 2;
+pub const STT_SECTION: u8 = 3;
+pub const STT_FILE: u8 = 4;
+pub const STT_COMMON: u8 = 5;
+pub const STT_TLS: u8 = 6;
+
+pub const STV_DEFAULT: u8 = 0;
+pub const STV_INTERNAL: u8 = 1;
+pub const STV_HIDDEN: u8 = 2;
+pub const STV_PROTECTED: u8 = 3;
+
+pub const SHN_UNDEF: u16 = 0;
+pub const SHN_LORESERVE: u16 = 0xff00;
+pub const SHN_ABS: u16 = 0xfff1;
+pub const SHN_COMMON: u16 = 0xfff2;
+pub const SHN_XINDEX: u16 = 0xffff;
+
+pub(crate) fn parse_symtab_shndx(reader: &ElfReader, offset: usize) -> Option<ElfWord> {
+    reader.cursor(offset, ELF_SYMTAB_SHNDX_SIZE)?.u32()
+}
+
+impl ElfSymbol {
+    pub(crate) fn entry_size(reader: &ElfReader) -> usize {
+        if reader.is_32bit() {
+            ELF32_SYMBOL_SIZE
+        } else {
+            ELF64_SYMBOL_SIZE
+        }
+    }
 
-const STT_NOTYPE: u8 = 0;
-const STT_OBJECT: u8 = 1;
-const STT_FUNC: u8 = 2;
-const STT_SECTION: u8 = 3;
-const STT_FILE: u8 = 4;
-const STT_COMMON: u8 = 5;
-const STT_TLS: u8 = 6;
+    pub(crate) fn parse(reader: &ElfReader, offset: usize) -> Option<Self> {
+        let mut c = reader.cursor(offset, Self::entry_size(reader))?;
+        if reader.is_32bit() {
+            let name = c.u32()?;
+            let value = c.addr()?;
+            let size = c.addr()?;
+            let info = c.u8()?;
+            let other = c.u8()?;
+            let section_index = c.u16()?;
+            Some(Self {
+                name,
+                info,
+                other,
+                section_index,
+                value,
+                size,
+            })
+        } else {
+            Some(Self {
+                name: c.u32()?,
+                info: c.u8()?,
+                other: c.u8()?,
+                section_index: c.u16()?,
+                value: c.addr()?,
+                size: c.addr()?,
+            })
+        }
+    }
+}
 
-const STV_DEFAULT: u8 = 0;
-const STV_INTERNAL: u8 = 1;
-const STV_HIDDEN: u8 = 2;
-const STV_PROTECTED: u8 = 3;
+impl ElfSymbolTable<'_> {
+    /// Returns the index of the section symbol `index` is defined in,
+    /// following `SHN_XINDEX` into the `SHT_SYMTAB_SHNDX` section. Reserved
+    /// indexes such as `SHN_ABS` are returned as they are.
+    pub fn section_index(&self, index: usize, symbol: &ElfSymbol) -> usize {
+        if symbol.section_index != SHN_XINDEX {
+            return symbol.section_index as usize;
+        }
+        self.extended_section_indexes
+            .and_then(|table| table.get(index))
+            .unwrap_or(0) as usize
+    }
+}
 
-const SHN_UNDEF: u16 = 0;
-const SHN_ABS: u16 = 0xfff1;
-const SHN_COMMON: u16 = 0xfff2;
+impl<'data> ElfFile<'data> {
+    /// Returns the symbol table stored in section `index`, if any.
+    pub fn get_symbol_table(&self, index: usize) -> Option<&ElfSymbolTable<'data>> {
+        self.symbol_tables.iter().find(|st| st.index == index)
+    }
 
-impl ElfFile {
     pub fn show_symbol_tables(&self) {
-        let shstrtab = self.section_headers[self.header.string_table_index as usize];
+        self.warn_table_errors(ElfTable::Symbols);
         for (i, st) in self.symbol_tables.iter().enumerate() {
             if i != 0 {
                 println!();
             }
-            let section_name =
-                self.get_name_from_strtab(&shstrtab, self.section_headers[st.index].name as usize);
-            let strtab = &self.section_headers[st.index + 1];
-            self.show_symbols(&section_name, &st, strtab);
+            let section = match self.section_headers.get(st.index) {
+                Some(section) => section,
+                None => continue,
+            };
+            if let Some(warning) = self.get_link_warning(st.index, &section) {
+                eprintln!("readelf: Warning: {}", warning);
+            }
+            let strtab = match self.get_linked_section(&section) {
+                Some(strtab) => strtab,
+                None => continue,
+            };
+            let section_name = self.get_section_name(&section);
+            self.show_symbols(&section_name, st, &strtab);
         }
     }
 
-    fn show_symbols(&self, section_name: &String, st: &ElfSymbolTable, strtab: &ElfSectionHeader) {
+    fn show_symbols(&self, section_name: &str, st: &ElfSymbolTable, strtab: &ElfSectionHeader) {
         println!("Symbol tables '{}': ", section_name);
 
         let mut table = Table::new();
         table.set_titles(row![
             "Num", "Value", "Size", "Type", "Bind", "Vis", "Ndx", "Name",
         ]);
+        let versions = self.symbol_version_suffixes(st);
         for (i, symbol) in st.symbols.iter().enumerate() {
+            let mut name = self.get_name_from_strtab(strtab, symbol.name as usize);
+            // Version definitions come with an absolute symbol of the same
+            // name, which readelf prints without a suffix.
+            match versions.get(i) {
+                Some(suffix) if suffix.trim_start_matches('@') != name => name.push_str(suffix),
+                _ => {}
+            }
             table.add_row(row![
                 format!("{}", i),
                 format!("{:X}", symbol.value),
@@ -76,8 +165,8 @@ impl ElfFile {
                 self.get_symbol_type(symbol.info),
                 self.get_symbol_binding(symbol.info),
                 self.get_symbol_visibility(symbol.other),
-                self.get_symbol_index_type(symbol.section_index),
-                self.get_name_from_strtab(strtab, symbol.name as usize),
+                self.get_symbol_index_type(st, i, &symbol),
+                name,
             ]);
         }
         table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
@@ -119,12 +208,18 @@ impl ElfFile {
         }
     }
 
-    fn get_symbol_index_type(&self, type_: u16) -> String {
-        match type_ {
+    fn get_symbol_index_type(
+        &self,
+        st: &ElfSymbolTable,
+        index: usize,
+        symbol: &ElfSymbol,
+    ) -> String {
+        match symbol.section_index {
             SHN_UNDEF => "UND".to_string(),
             SHN_ABS => "ABS".to_string(),
             SHN_COMMON => "COM".to_string(),
-            _ => format!("{:3}", type_),
+            SHN_XINDEX => format!("{:3}", st.section_index(index, symbol)),
+            _ => format!("{:3}", symbol.section_index),
         }
     }
 }
@@ -0,0 +1,194 @@
+use std::{error, fmt, io};
+
+/// The table an out-of-range entry was found in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ElfTable {
+    Header,
+    SectionHeaders,
+    ProgramHeaders,
+    Symbols,
+    Relocations,
+    Dynamic,
+    Versions,
+    SectionData,
+}
+
+impl fmt::Display for ElfTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ElfTable::Header => write!(f, "ELF header"),
+            ElfTable::SectionHeaders => write!(f, "section header table"),
+            ElfTable::ProgramHeaders => write!(f, "program header table"),
+            ElfTable::Symbols => write!(f, "symbol table"),
+            ElfTable::Relocations => write!(f, "relocation section"),
+            ElfTable::Dynamic => write!(f, "dynamic section"),
+            ElfTable::Versions => write!(f, "version section"),
+            ElfTable::SectionData => write!(f, "section data"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ElfError {
+    Io(io::Error),
+    BadMagic,
+    UnsupportedClass(u8),
+    UnsupportedEncoding(u8),
+    UnsupportedVersion(u8),
+    /// A size field of the ELF header does not match the structure size
+    /// implied by the file class.
+    BadHeaderSize {
+        field: &'static str,
+        size: u16,
+        expected: u16,
+    },
+    /// Entry `index` of `table` (or the table itself, when `index` is the
+    /// section it lives in) covers `offset..offset + size`, which lies outside
+    /// the file.
+    OutOfRange {
+        table: ElfTable,
+        index: usize,
+        offset: u64,
+        size: u64,
+    },
+    BadEntrySize {
+        table: ElfTable,
+        index: usize,
+        size: u64,
+    },
+    BadStringTableIndex(usize),
+    NoSuchSection(usize),
+    UnsupportedCompression {
+        index: usize,
+        compression_type: u32,
+    },
+    /// Section `index` is compressed but its contents could not be inflated.
+    Decompress {
+        index: usize,
+        message: String,
+    },
+    /// Entry `index` of `table` could not be placed when writing a file
+    /// without overlapping the data before it.
+    Overlap {
+        table: ElfTable,
+        index: usize,
+    },
+    /// Entry `index` of `table` would end past the largest offset or address
+    /// the file can hold when writing it.
+    LayoutOverflow {
+        table: ElfTable,
+        index: usize,
+    },
+    /// The file being written would be too large to build in memory.
+    OutputTooLarge(u64),
+    /// The file has no dynamic section to edit.
+    NotDynamic,
+    /// The file has no `PT_INTERP` segment to edit.
+    NoInterpreter,
+    /// No `DT_NEEDED` entry names the library.
+    NoSuchLibrary(String),
+    /// The file has no `PT_LOAD` segment to place relocated data after.
+    NoLoadSegment,
+}
+
+impl ElfError {
+    /// Returns the table the error was found in, if it concerns one.
+    pub fn table(&self) -> Option<ElfTable> {
+        match self {
+            ElfError::OutOfRange { table, .. }
+            | ElfError::BadEntrySize { table, .. }
+            | ElfError::Overlap { table, .. }
+            | ElfError::LayoutOverflow { table, .. } => Some(*table),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ElfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ElfError::Io(e) => write!(f, "{}", e),
+            ElfError::BadMagic => write!(f, "not an ELF file - it has the wrong magic bytes"),
+            ElfError::UnsupportedClass(class) => write!(f, "unsupported ELF class: {}", class),
+            ElfError::UnsupportedEncoding(encoding) => {
+                write!(f, "unsupported ELF data encoding: {}", encoding)
+            }
+            ElfError::UnsupportedVersion(version) => {
+                write!(f, "unsupported ELF version: {}", version)
+            }
+            ElfError::BadHeaderSize {
+                field,
+                size,
+                expected,
+            } => write!(
+                f,
+                "{} is {} bytes, expected {} for this ELF class",
+                field, size, expected
+            ),
+            ElfError::OutOfRange {
+                table,
+                index,
+                offset,
+                size,
+            } => write!(
+                f,
+                "{} entry {} at offset 0x{:x} (size 0x{:x}) is out of range",
+                table, index, offset, size
+            ),
+            ElfError::BadEntrySize { table, index, size } => {
+                write!(f, "{} {} has invalid entry size {}", table, index, size)
+            }
+            ElfError::BadStringTableIndex(index) => {
+                write!(
+                    f,
+                    "section header string table index {} is out of range",
+                    index
+                )
+            }
+            ElfError::NoSuchSection(index) => write!(f, "section {} does not exist", index),
+            ElfError::UnsupportedCompression {
+                index,
+                compression_type,
+            } => write!(
+                f,
+                "section {} uses unsupported compression type {}",
+                index, compression_type
+            ),
+            ElfError::Decompress { index, message } => {
+                write!(f, "unable to decompress section {}: {}", index, message)
+            }
+            ElfError::Overlap { table, index } => write!(
+                f,
+                "{} entry {} overlaps the data laid out before it",
+                table, index
+            ),
+            ElfError::LayoutOverflow { table, index } => write!(
+                f,
+                "{} entry {} does not fit in the file when laid out",
+                table, index
+            ),
+            ElfError::OutputTooLarge(size) => {
+                write!(f, "unable to allocate {} bytes for the output file", size)
+            }
+            ElfError::NotDynamic => write!(f, "file has no dynamic section"),
+            ElfError::NoInterpreter => write!(f, "file has no program interpreter"),
+            ElfError::NoSuchLibrary(name) => write!(f, "no DT_NEEDED entry for {}", name),
+            ElfError::NoLoadSegment => write!(f, "file has no loadable segment"),
+        }
+    }
+}
+
+impl error::Error for ElfError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            ElfError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ElfError {
+    fn from(e: io::Error) -> Self {
+        ElfError::Io(e)
+    }
+}
@@ -0,0 +1,122 @@
+use crate::elf::elf_symbol::STT_SECTION;
+use crate::elf::section_header::SHT_GROUP;
+use crate::elf::*;
+
+use prettytable::{format, row, Table};
+
+/// A decoded `SHT_GROUP` section.
+#[derive(Clone, Debug)]
+pub struct ElfGroup {
+    pub index: usize,
+    pub flags: ElfWord,
+    pub signature: String,
+    pub members: Vec<usize>,
+}
+
+pub const GRP_COMDAT: u32 = 0x1;
+pub const GRP_MASKOS: u32 = 0x0ff00000;
+pub const GRP_MASKPROC: u32 = 0xf0000000;
+
+impl ElfGroup {
+    pub fn is_comdat(&self) -> bool {
+        self.flags & GRP_COMDAT != 0
+    }
+}
+
+impl ElfFile<'_> {
+    pub fn groups(&self) -> Vec<ElfGroup> {
+        self.section_headers
+            .iter()
+            .enumerate()
+            .filter(|(_, sh)| sh.section_type == SHT_GROUP)
+            .filter_map(|(i, sh)| self.read_group(i, &sh))
+            .collect()
+    }
+
+    /// Decodes a group section: a flag word followed by the indexes of the
+    /// member sections. The signature is the name of symbol `sh_info` in the
+    /// symbol table `sh_link`.
+    fn read_group(&self, index: usize, sh: &ElfSectionHeader) -> Option<ElfGroup> {
+        let data = self.section_data(sh)?;
+        let mut c = self.reader().cursor_over(data);
+        let flags = c.u32()?;
+        let mut members = Vec::new();
+        while let Some(member) = c.u32() {
+            members.push(member as usize);
+        }
+        Some(ElfGroup {
+            index,
+            flags,
+            signature: self.get_group_signature(sh).unwrap_or_default(),
+            members,
+        })
+    }
+
+    fn get_group_signature(&self, sh: &ElfSectionHeader) -> Option<String> {
+        let symtab = self.get_linked_section(sh)?;
+        let strtab = self.get_linked_section(&symtab)?;
+        let st = self.get_symbol_table(sh.link as usize)?;
+        let symbol = st.symbols.get(sh.info as usize)?;
+        if symbol.info & 0xf == STT_SECTION {
+            let section = self
+                .section_headers
+                .get(st.section_index(sh.info as usize, &symbol))?;
+            return Some(self.get_section_name(&section));
+        }
+        Some(self.get_name_from_strtab(&strtab, symbol.name as usize))
+    }
+
+    pub fn show_groups(&self) {
+        let groups = self.groups();
+        if groups.is_empty() {
+            println!("There are no section groups in this file.");
+            return;
+        }
+        for (i, group) in groups.iter().enumerate() {
+            if i != 0 {
+                println!();
+            }
+            let name = match self.section_headers.get(group.index) {
+                Some(sh) => self.get_section_name(&sh),
+                None => String::new(),
+            };
+            let flags = self.get_group_flags(group.flags);
+            println!(
+                "{}{}group section [{}] `{}' [{}] contains {} sections:",
+                flags,
+                if flags.is_empty() { "" } else { " " },
+                group.index,
+                name,
+                group.signature,
+                group.members.len()
+            );
+
+            let mut table = Table::new();
+            table.set_titles(row!["Index", "Name"]);
+            for &member in group.members.iter() {
+                let member_name = match self.section_headers.get(member) {
+                    Some(sh) => self.get_section_name(&sh),
+                    None => "<corrupt>".to_string(),
+                };
+                table.add_row(row![member, member_name]);
+            }
+            table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+            table.printstd();
+        }
+    }
+
+    /// Names the set group flags. A group without flags gets an empty
+    /// string, as in binutils; bits other than `GRP_COMDAT` are reported as
+    /// `UNKNOWN` next to the known names.
+    fn get_group_flags(&self, flags: u32) -> String {
+        let mut names = Vec::new();
+        if flags & GRP_COMDAT != 0 {
+            names.push("COMDAT".to_string());
+        }
+        let unknown = flags & !GRP_COMDAT;
+        if unknown != 0 {
+            names.push(format!("UNKNOWN (0x{:x})", unknown));
+        }
+        names.join(", ")
+    }
+}
@@ -0,0 +1,351 @@
+use crate::elf::elf_symbol::{ElfSymbol, SHN_UNDEF};
+use crate::elf::reader::ElfReader;
+use crate::elf::section_header::{SHT_GNU_HASH, SHT_HASH};
+use crate::elf::table::LazyTable;
+use crate::elf::*;
+
+use prettytable::{format, row, Table};
+
+/// A `SHT_HASH` or `SHT_GNU_HASH` section together with the index of the
+/// symbol table it indexes.
+#[derive(Debug)]
+pub struct ElfHashTable<'data> {
+    pub index: usize,
+    pub symtab_index: usize,
+    pub kind: ElfHashKind<'data>,
+}
+
+#[derive(Debug)]
+pub enum ElfHashKind<'data> {
+    SysV {
+        buckets: LazyTable<'data, ElfWord>,
+        chains: LazyTable<'data, ElfWord>,
+    },
+    Gnu {
+        symbol_offset: ElfWord,
+        bloom_shift: ElfWord,
+        bloom: LazyTable<'data, ElfXword>,
+        buckets: LazyTable<'data, ElfWord>,
+        chains: LazyTable<'data, ElfWord>,
+    },
+}
+
+fn parse_word(reader: &ElfReader, offset: usize) -> Option<ElfWord> {
+    reader.cursor(offset, 4)?.u32()
+}
+
+fn parse_xword_as_word(reader: &ElfReader, offset: usize) -> Option<ElfWord> {
+    reader.cursor(offset, 8)?.u64().map(|v| v as ElfWord)
+}
+
+fn parse_bloom_word(reader: &ElfReader, offset: usize) -> Option<ElfXword> {
+    reader
+        .cursor(offset, if reader.is_32bit() { 4 } else { 8 })?
+        .addr()
+}
+
+/// The System V ABI hash function used by `SHT_HASH`.
+pub fn sysv_hash(name: &[u8]) -> u32 {
+    name.iter().fold(0u32, |h, &c| {
+        let h = (h << 4).wrapping_add(c as u32);
+        let g = h & 0xf000_0000;
+        (h ^ (g >> 24)) & !g
+    })
+}
+
+/// The hash function used by `SHT_GNU_HASH` (Bernstein's djb2).
+pub fn gnu_hash(name: &[u8]) -> u32 {
+    name.iter()
+        .fold(5381u32, |h, &c| h.wrapping_mul(33).wrapping_add(c as u32))
+}
+
+impl<'data> ElfHashTable<'data> {
+    fn parse(
+        reader: ElfReader<'data>,
+        index: usize,
+        sh: &ElfSectionHeader,
+    ) -> Option<ElfHashTable<'data>> {
+        if !reader.contains_table(sh.offset, sh.size, 1) {
+            return None;
+        }
+        let kind = if sh.section_type == SHT_HASH {
+            Self::parse_sysv(reader, sh)?
+        } else {
+            Self::parse_gnu(reader, sh)?
+        };
+        Some(ElfHashTable {
+            index,
+            symtab_index: sh.link as usize,
+            kind,
+        })
+    }
+
+    fn parse_sysv(reader: ElfReader<'data>, sh: &ElfSectionHeader) -> Option<ElfHashKind<'data>> {
+        // s390x and Alpha use 8-byte hash entries.
+        let word_size: u64 = if sh.entry_size == 8 { 8 } else { 4 };
+        let parse: fn(&ElfReader, usize) -> Option<ElfWord> = if word_size == 8 {
+            parse_xword_as_word
+        } else {
+            parse_word
+        };
+        let nbucket = parse(&reader, sh.offset as usize)? as u64;
+        let nchain = parse(&reader, (sh.offset + word_size) as usize)? as u64;
+
+        let buckets_offset = sh.offset + 2 * word_size;
+        let chains_offset = buckets_offset.checked_add(nbucket.checked_mul(word_size)?)?;
+        let end = chains_offset.checked_add(nchain.checked_mul(word_size)?)?;
+        if end > sh.offset + sh.size {
+            return None;
+        }
+        Some(ElfHashKind::SysV {
+            buckets: LazyTable::new(
+                reader,
+                buckets_offset as usize,
+                word_size as usize,
+                nbucket as usize,
+                parse,
+            ),
+            chains: LazyTable::new(
+                reader,
+                chains_offset as usize,
+                word_size as usize,
+                nchain as usize,
+                parse,
+            ),
+        })
+    }
+
+    /// Decodes a GNU hash section. The chain array has no explicit length
+    /// and runs to the end of the section.
+    fn parse_gnu(reader: ElfReader<'data>, sh: &ElfSectionHeader) -> Option<ElfHashKind<'data>> {
+        let mut c = reader.cursor(sh.offset as usize, 16)?;
+        let nbucket = c.u32()? as u64;
+        let symbol_offset = c.u32()?;
+        let bloom_size = c.u32()? as u64;
+        let bloom_shift = c.u32()?;
+        let bloom_word: u64 = if reader.is_32bit() { 4 } else { 8 };
+
+        let bloom_offset = sh.offset + 16;
+        let buckets_offset = bloom_offset.checked_add(bloom_size.checked_mul(bloom_word)?)?;
+        let chains_offset = buckets_offset.checked_add(nbucket.checked_mul(4)?)?;
+        let end = sh.offset + sh.size;
+        if chains_offset > end {
+            return None;
+        }
+        Some(ElfHashKind::Gnu {
+            symbol_offset,
+            bloom_shift,
+            bloom: LazyTable::new(
+                reader,
+                bloom_offset as usize,
+                bloom_word as usize,
+                bloom_size as usize,
+                parse_bloom_word,
+            ),
+            buckets: LazyTable::new(
+                reader,
+                buckets_offset as usize,
+                4,
+                nbucket as usize,
+                parse_word,
+            ),
+            chains: LazyTable::new(
+                reader,
+                chains_offset as usize,
+                4,
+                ((end - chains_offset) / 4) as usize,
+                parse_word,
+            ),
+        })
+    }
+
+    pub fn is_gnu(&self) -> bool {
+        matches!(self.kind, ElfHashKind::Gnu { .. })
+    }
+
+    pub fn bucket_count(&self) -> usize {
+        match &self.kind {
+            ElfHashKind::SysV { buckets, .. } | ElfHashKind::Gnu { buckets, .. } => buckets.len(),
+        }
+    }
+
+    /// Returns the symbol indexes chained from `bucket`, in lookup order.
+    pub fn bucket_symbols(&self, bucket: usize) -> Vec<usize> {
+        let mut symbols = Vec::new();
+        match &self.kind {
+            ElfHashKind::SysV { buckets, chains } => {
+                let mut index = buckets.get(bucket).unwrap_or(0) as usize;
+                // The chain can never be longer than the table, so a longer
+                // walk means the chain loops.
+                while index != 0 && symbols.len() < chains.len() {
+                    symbols.push(index);
+                    index = match chains.get(index) {
+                        Some(next) => next as usize,
+                        None => break,
+                    };
+                }
+            }
+            ElfHashKind::Gnu {
+                symbol_offset,
+                buckets,
+                chains,
+                ..
+            } => {
+                let symbol_offset = *symbol_offset as usize;
+                let mut index = buckets.get(bucket).unwrap_or(0) as usize;
+                if index < symbol_offset {
+                    return symbols;
+                }
+                while let Some(hash) = chains.get(index - symbol_offset) {
+                    symbols.push(index);
+                    if hash & 1 != 0 {
+                        break;
+                    }
+                    index += 1;
+                }
+            }
+        }
+        symbols
+    }
+
+    /// Returns the length of every bucket chain.
+    pub fn chain_lengths(&self) -> Vec<usize> {
+        (0..self.bucket_count())
+            .map(|bucket| self.bucket_symbols(bucket).len())
+            .collect()
+    }
+
+    /// Checks the GNU bloom filter, which rejects most names that are not in
+    /// the table without touching the buckets.
+    fn bloom_accepts(&self, hash: u32, is_32bit: bool) -> bool {
+        let (bloom, bloom_shift) = match &self.kind {
+            ElfHashKind::Gnu {
+                bloom, bloom_shift, ..
+            } => (bloom, *bloom_shift),
+            ElfHashKind::SysV { .. } => return true,
+        };
+        if bloom.is_empty() {
+            return false;
+        }
+        let bits = if is_32bit { 32 } else { 64 };
+        let word = match bloom.get((hash / bits) as usize % bloom.len()) {
+            Some(word) => word,
+            None => return false,
+        };
+        let mask =
+            (1u64 << (hash % bits)) | (1u64 << (hash.checked_shr(bloom_shift).unwrap_or(0) % bits));
+        word & mask == mask
+    }
+}
+
+impl<'data> ElfFile<'data> {
+    pub fn hash_tables(&self) -> Vec<ElfHashTable<'data>> {
+        self.section_headers
+            .iter()
+            .enumerate()
+            .filter(|(_, sh)| matches!(sh.section_type, SHT_HASH | SHT_GNU_HASH))
+            .filter_map(|(i, sh)| ElfHashTable::parse(self.reader(), i, &sh))
+            .collect()
+    }
+
+    /// Looks `name` up in the dynamic symbol table through its hash table,
+    /// the way the dynamic loader does, preferring `SHT_GNU_HASH`. Returns
+    /// the symbol index and the symbol.
+    pub fn lookup_dynamic_symbol(&self, name: &str) -> Option<(usize, ElfSymbol)> {
+        let mut tables = self.hash_tables();
+        tables.sort_by_key(|ht| !ht.is_gnu());
+        let ht = tables.into_iter().next()?;
+        self.lookup_symbol_in(&ht, name)
+    }
+
+    fn lookup_symbol_in(&self, ht: &ElfHashTable, name: &str) -> Option<(usize, ElfSymbol)> {
+        let symtab = self.section_headers.get(ht.symtab_index)?;
+        let strtab = self.get_linked_section(&symtab)?;
+        let symbols = &self.get_symbol_table(ht.symtab_index)?.symbols;
+
+        let name = name.as_bytes();
+        let hash = if ht.is_gnu() {
+            gnu_hash(name)
+        } else {
+            sysv_hash(name)
+        };
+        if !ht.bloom_accepts(hash, self.is_32bit()) || ht.bucket_count() == 0 {
+            return None;
+        }
+        let chains = match &ht.kind {
+            ElfHashKind::Gnu {
+                symbol_offset,
+                chains,
+                ..
+            } => Some((*symbol_offset as usize, chains)),
+            ElfHashKind::SysV { .. } => None,
+        };
+
+        let bucket = hash as usize % ht.bucket_count();
+        ht.bucket_symbols(bucket).into_iter().find_map(|index| {
+            if let Some((symbol_offset, chains)) = chains {
+                let chain_hash = chains.get(index - symbol_offset)?;
+                if chain_hash | 1 != hash | 1 {
+                    return None;
+                }
+            }
+            let symbol = symbols.get(index)?;
+            let symbol_name = self.get_name_from_strtab(&strtab, symbol.name as usize);
+            if symbol.section_index != SHN_UNDEF && symbol_name.as_bytes() == name {
+                Some((index, symbol))
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn show_hash_histograms(&self) {
+        let hash_tables = self.hash_tables();
+        if hash_tables.is_empty() {
+            println!("There are no hash tables in this file.");
+            return;
+        }
+        for (i, ht) in hash_tables.iter().enumerate() {
+            if i != 0 {
+                println!();
+            }
+            let section_name = match self.section_headers.get(ht.index) {
+                Some(sh) => self.get_section_name(&sh),
+                None => String::new(),
+            };
+            let lengths = ht.chain_lengths();
+            println!(
+                "Histogram for `{}' bucket list length (total of {} buckets):",
+                section_name,
+                lengths.len()
+            );
+
+            let max_length = lengths.iter().copied().max().unwrap_or(0);
+            let mut counts = vec![0usize; max_length + 1];
+            for &length in lengths.iter() {
+                counts[length] += 1;
+            }
+            let total_symbols: usize = lengths.iter().sum();
+
+            let mut table = Table::new();
+            table.set_titles(row!["Length", "Number", "% of total", "Coverage"]);
+            let mut covered = 0;
+            for (length, &count) in counts.iter().enumerate() {
+                covered += length * count;
+                let coverage = if length == 0 || total_symbols == 0 {
+                    String::new()
+                } else {
+                    format!("{:5.1}%", covered as f64 * 100.0 / total_symbols as f64)
+                };
+                table.add_row(row![
+                    format!("{}", length),
+                    format!("{}", count),
+                    format!("({:5.1}%)", count as f64 * 100.0 / lengths.len() as f64),
+                    coverage,
+                ]);
+            }
+            table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+            table.printstd();
+        }
+    }
+}
@@ -0,0 +1,324 @@
+use crate::elf::common::get_flag_names;
+use crate::elf::elf_machine::ElfMachine;
+
+pub const EF_ARM_EABIMASK: u32 = 0xff000000;
+pub const EF_ARM_EABI_UNKNOWN: u32 = 0x00000000;
+pub const EF_ARM_EABI_VER1: u32 = 0x01000000;
+pub const EF_ARM_EABI_VER2: u32 = 0x02000000;
+pub const EF_ARM_EABI_VER3: u32 = 0x03000000;
+pub const EF_ARM_EABI_VER4: u32 = 0x04000000;
+pub const EF_ARM_EABI_VER5: u32 = 0x05000000;
+pub const EF_ARM_RELEXEC: u32 = 0x01;
+pub const EF_ARM_INTERWORK: u32 = 0x04;
+pub const EF_ARM_SYMSARESORTED: u32 = 0x04;
+pub const EF_ARM_APCS_26: u32 = 0x08;
+pub const EF_ARM_DYNSYMSUSESEGIDX: u32 = 0x08;
+pub const EF_ARM_APCS_FLOAT: u32 = 0x10;
+pub const EF_ARM_MAPSYMSFIRST: u32 = 0x10;
+pub const EF_ARM_PIC: u32 = 0x20;
+pub const EF_ARM_ALIGN8: u32 = 0x40;
+pub const EF_ARM_NEW_ABI: u32 = 0x80;
+pub const EF_ARM_OLD_ABI: u32 = 0x100;
+pub const EF_ARM_SOFT_FLOAT: u32 = 0x200;
+pub const EF_ARM_ABI_FLOAT_SOFT: u32 = 0x200;
+pub const EF_ARM_VFP_FLOAT: u32 = 0x400;
+pub const EF_ARM_ABI_FLOAT_HARD: u32 = 0x400;
+pub const EF_ARM_MAVERICK_FLOAT: u32 = 0x800;
+pub const EF_ARM_LE8: u32 = 0x00400000;
+pub const EF_ARM_BE8: u32 = 0x00800000;
+
+pub const EF_RISCV_RVC: u32 = 0x0001;
+pub const EF_RISCV_FLOAT_ABI: u32 = 0x0006;
+pub const EF_RISCV_FLOAT_ABI_SOFT: u32 = 0x0000;
+pub const EF_RISCV_FLOAT_ABI_SINGLE: u32 = 0x0002;
+pub const EF_RISCV_FLOAT_ABI_DOUBLE: u32 = 0x0004;
+pub const EF_RISCV_FLOAT_ABI_QUAD: u32 = 0x0006;
+pub const EF_RISCV_RVE: u32 = 0x0008;
+pub const EF_RISCV_TSO: u32 = 0x0010;
+
+pub const EF_MIPS_NOREORDER: u32 = 0x00000001;
+pub const EF_MIPS_PIC: u32 = 0x00000002;
+pub const EF_MIPS_CPIC: u32 = 0x00000004;
+pub const EF_MIPS_UCODE: u32 = 0x00000010;
+pub const EF_MIPS_ABI2: u32 = 0x00000020;
+pub const EF_MIPS_OPTIONS_FIRST: u32 = 0x00000080;
+pub const EF_MIPS_32BITMODE: u32 = 0x00000100;
+pub const EF_MIPS_FP64: u32 = 0x00000200;
+pub const EF_MIPS_NAN2008: u32 = 0x00000400;
+pub const EF_MIPS_MACH: u32 = 0x00ff0000;
+pub const EF_MIPS_ABI: u32 = 0x0000f000;
+pub const EF_MIPS_ABI_O32: u32 = 0x00001000;
+pub const EF_MIPS_ABI_O64: u32 = 0x00002000;
+pub const EF_MIPS_ABI_EABI32: u32 = 0x00003000;
+pub const EF_MIPS_ABI_EABI64: u32 = 0x00004000;
+pub const EF_MIPS_ARCH_ASE_MICROMIPS: u32 = 0x02000000;
+pub const EF_MIPS_ARCH_ASE_M16: u32 = 0x04000000;
+pub const EF_MIPS_ARCH_ASE_MDMX: u32 = 0x08000000;
+pub const EF_MIPS_ARCH: u32 = 0xf0000000;
+
+pub const EF_PPC_RELOCATABLE_LIB: u32 = 0x00008000;
+pub const EF_PPC_RELOCATABLE: u32 = 0x00010000;
+pub const EF_PPC_EMB: u32 = 0x80000000;
+pub const EF_PPC64_ABI: u32 = 0x00000003;
+
+pub const EF_LOONGARCH_ABI_MODIFIER_MASK: u32 = 0x07;
+pub const EF_LOONGARCH_ABI_SOFT_FLOAT: u32 = 0x01;
+pub const EF_LOONGARCH_ABI_SINGLE_FLOAT: u32 = 0x02;
+pub const EF_LOONGARCH_ABI_DOUBLE_FLOAT: u32 = 0x03;
+pub const EF_LOONGARCH_OBJABI_MASK: u32 = 0xc0;
+pub const EF_LOONGARCH_OBJABI_V0: u32 = 0x00;
+pub const EF_LOONGARCH_OBJABI_V1: u32 = 0x40;
+
+const MIPS_MACHINES: &[(u32, &str)] = &[
+    (0x00810000, "3900"),
+    (0x00820000, "4010"),
+    (0x00830000, "4100"),
+    (0x00850000, "4650"),
+    (0x00870000, "4120"),
+    (0x00880000, "4111"),
+    (0x008a0000, "sb1"),
+    (0x008b0000, "octeon"),
+    (0x008c0000, "xlr"),
+    (0x008d0000, "octeon2"),
+    (0x008e0000, "octeon3"),
+    (0x00910000, "5400"),
+    (0x00920000, "5900"),
+    (0x00980000, "5500"),
+    (0x00990000, "9000"),
+    (0x00a00000, "loongson-2e"),
+    (0x00a10000, "loongson-2f"),
+    (0x00a20000, "gs464"),
+    (0x00a30000, "gs464e"),
+    (0x00a40000, "gs264e"),
+];
+
+const MIPS_ARCHS: &[&str] = &[
+    "mips1", "mips2", "mips3", "mips4", "mips5", "mips32", "mips64", "mips32r2", "mips64r2",
+    "mips32r6", "mips64r6",
+];
+
+const MIPS_FLAG_NAMES: &[(u32, &str)] = &[
+    (EF_MIPS_NOREORDER, "noreorder"),
+    (EF_MIPS_PIC, "pic"),
+    (EF_MIPS_CPIC, "cpic"),
+    (EF_MIPS_UCODE, "ugen_reserved"),
+    (EF_MIPS_ABI2, "abi2"),
+    (EF_MIPS_OPTIONS_FIRST, "odk first"),
+    (EF_MIPS_32BITMODE, "32bitmode"),
+    (EF_MIPS_NAN2008, "nan2008"),
+    (EF_MIPS_FP64, "fp64"),
+];
+
+const MIPS_ASE_NAMES: &[(u32, &str)] = &[
+    (EF_MIPS_ARCH_ASE_MDMX, "mdmx"),
+    (EF_MIPS_ARCH_ASE_M16, "mips16"),
+    (EF_MIPS_ARCH_ASE_MICROMIPS, "micromips"),
+];
+
+const PPC_FLAG_NAMES: &[(u32, &str)] = &[
+    (EF_PPC_EMB, "emb"),
+    (EF_PPC_RELOCATABLE, "relocatable"),
+    (EF_PPC_RELOCATABLE_LIB, "relocatable-lib"),
+];
+
+/// Decodes the processor-specific `e_flags` of the ELF header into the names
+/// GNU readelf prints after the hex value.
+pub fn get_header_flag_names(machine: ElfMachine, flags: u32) -> Vec<String> {
+    if flags == 0 {
+        return Vec::new();
+    }
+    match machine {
+        ElfMachine::Arm => get_arm_flag_names(flags),
+        ElfMachine::Riscv => get_riscv_flag_names(flags),
+        ElfMachine::Mips | ElfMachine::MipsRs3Le => get_mips_flag_names(flags),
+        ElfMachine::Ppc => get_ppc_flag_names(flags),
+        ElfMachine::Ppc64 => get_ppc64_flag_names(flags),
+        ElfMachine::Loongarch => get_loongarch_flag_names(flags),
+        _ => Vec::new(),
+    }
+}
+
+/// Appends the names of `flags`, and any bits `known` does not cover, as one
+/// comma-separated entry.
+fn push_flags(names: &mut Vec<String>, flags: u32, known: &[(u32, &str)]) {
+    let flag_names = get_flag_names(flags, known, ", ");
+    if !flag_names.is_empty() {
+        names.push(flag_names);
+    }
+}
+
+/// The bits named by `known`, for fields whose other bits are decoded
+/// separately.
+fn known_bits(known: &[(u32, &str)]) -> u32 {
+    known.iter().fold(0, |acc, &(flag, _)| acc | flag)
+}
+
+fn get_arm_flag_names(flags: u32) -> Vec<String> {
+    let mut names = Vec::new();
+    let rest = flags & !EF_ARM_EABIMASK;
+    match flags & EF_ARM_EABIMASK {
+        EF_ARM_EABI_UNKNOWN => {
+            names.push("GNU EABI".to_string());
+            push_flags(
+                &mut names,
+                rest,
+                &[
+                    (EF_ARM_RELEXEC, "relocatable executable"),
+                    (EF_ARM_INTERWORK, "interworking enabled"),
+                    (EF_ARM_APCS_26, "uses APCS/26"),
+                    (EF_ARM_APCS_FLOAT, "uses APCS/float"),
+                    (EF_ARM_PIC, "position independent"),
+                    (EF_ARM_ALIGN8, "8 bit structure alignment"),
+                    (EF_ARM_NEW_ABI, "uses new ABI"),
+                    (EF_ARM_OLD_ABI, "uses old ABI"),
+                    (EF_ARM_SOFT_FLOAT, "software FP"),
+                    (EF_ARM_VFP_FLOAT, "VFP"),
+                    (EF_ARM_MAVERICK_FLOAT, "Maverick FP"),
+                ],
+            );
+        }
+        EF_ARM_EABI_VER1 => {
+            names.push("Version1 EABI".to_string());
+            push_flags(
+                &mut names,
+                rest,
+                &[(EF_ARM_SYMSARESORTED, "sorted symbol tables")],
+            );
+        }
+        EF_ARM_EABI_VER2 => {
+            names.push("Version2 EABI".to_string());
+            push_flags(
+                &mut names,
+                rest,
+                &[
+                    (EF_ARM_SYMSARESORTED, "sorted symbol tables"),
+                    (EF_ARM_DYNSYMSUSESEGIDX, "dynamic symbols use segment index"),
+                    (EF_ARM_MAPSYMSFIRST, "mapping symbols precede others"),
+                ],
+            );
+        }
+        EF_ARM_EABI_VER3 => {
+            names.push("Version3 EABI".to_string());
+            push_flags(&mut names, rest, &[]);
+        }
+        EF_ARM_EABI_VER4 => {
+            names.push("Version4 EABI".to_string());
+            push_flags(
+                &mut names,
+                rest,
+                &[(EF_ARM_BE8, "BE8"), (EF_ARM_LE8, "LE8")],
+            );
+        }
+        EF_ARM_EABI_VER5 => {
+            names.push("Version5 EABI".to_string());
+            push_flags(
+                &mut names,
+                rest,
+                &[
+                    (EF_ARM_ABI_FLOAT_SOFT, "soft-float ABI"),
+                    (EF_ARM_ABI_FLOAT_HARD, "hard-float ABI"),
+                    (EF_ARM_BE8, "BE8"),
+                    (EF_ARM_LE8, "LE8"),
+                ],
+            );
+        }
+        _ => names.push("<EABI version unrecognised>".to_string()),
+    }
+    names
+}
+
+fn get_riscv_flag_names(flags: u32) -> Vec<String> {
+    let mut names = Vec::new();
+    if flags & EF_RISCV_RVC != 0 {
+        names.push("RVC".to_string());
+    }
+    if flags & EF_RISCV_RVE != 0 {
+        names.push("RVE".to_string());
+    }
+    if flags & EF_RISCV_TSO != 0 {
+        names.push("TSO".to_string());
+    }
+    names.push(
+        match flags & EF_RISCV_FLOAT_ABI {
+            EF_RISCV_FLOAT_ABI_SOFT => "soft-float ABI",
+            EF_RISCV_FLOAT_ABI_SINGLE => "single-float ABI",
+            EF_RISCV_FLOAT_ABI_DOUBLE => "double-float ABI",
+            _ => "quad-float ABI",
+        }
+        .to_string(),
+    );
+    names
+}
+
+fn get_mips_flag_names(flags: u32) -> Vec<String> {
+    let mut names = Vec::new();
+    push_flags(
+        &mut names,
+        flags & known_bits(MIPS_FLAG_NAMES),
+        MIPS_FLAG_NAMES,
+    );
+
+    let mach = flags & EF_MIPS_MACH;
+    if mach != 0 {
+        match MIPS_MACHINES.iter().find(|(value, _)| *value == mach) {
+            Some((_, name)) => names.push(name.to_string()),
+            None => names.push("unknown CPU".to_string()),
+        }
+    }
+
+    match flags & EF_MIPS_ABI {
+        0 => {}
+        EF_MIPS_ABI_O32 => names.push("o32".to_string()),
+        EF_MIPS_ABI_O64 => names.push("o64".to_string()),
+        EF_MIPS_ABI_EABI32 => names.push("eabi32".to_string()),
+        EF_MIPS_ABI_EABI64 => names.push("eabi64".to_string()),
+        _ => names.push("unknown ABI".to_string()),
+    }
+
+    push_flags(
+        &mut names,
+        flags & known_bits(MIPS_ASE_NAMES),
+        MIPS_ASE_NAMES,
+    );
+
+    match MIPS_ARCHS.get(((flags & EF_MIPS_ARCH) >> 28) as usize) {
+        Some(arch) => names.push(arch.to_string()),
+        None => names.push("unknown ISA".to_string()),
+    }
+    names
+}
+
+fn get_ppc_flag_names(flags: u32) -> Vec<String> {
+    let mut names = Vec::new();
+    push_flags(
+        &mut names,
+        flags & known_bits(PPC_FLAG_NAMES),
+        PPC_FLAG_NAMES,
+    );
+    names
+}
+
+fn get_ppc64_flag_names(flags: u32) -> Vec<String> {
+    let mut names = Vec::new();
+    if flags & EF_PPC64_ABI != 0 {
+        names.push(format!("abiv{}", flags & EF_PPC64_ABI));
+    }
+    push_flags(&mut names, flags & !EF_PPC64_ABI, &[]);
+    names
+}
+
+fn get_loongarch_flag_names(flags: u32) -> Vec<String> {
+    let mut names = Vec::new();
+    match flags & EF_LOONGARCH_ABI_MODIFIER_MASK {
+        EF_LOONGARCH_ABI_SOFT_FLOAT => names.push("SOFT-FLOAT".to_string()),
+        EF_LOONGARCH_ABI_SINGLE_FLOAT => names.push("SINGLE-FLOAT".to_string()),
+        EF_LOONGARCH_ABI_DOUBLE_FLOAT => names.push("DOUBLE-FLOAT".to_string()),
+        _ => {}
+    }
+    match flags & EF_LOONGARCH_OBJABI_MASK {
+        EF_LOONGARCH_OBJABI_V0 => names.push("OBJ-v0".to_string()),
+        EF_LOONGARCH_OBJABI_V1 => names.push("OBJ-v1".to_string()),
+        _ => {}
+    }
+    names
+}
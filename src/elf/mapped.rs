@@ -0,0 +1,13 @@
+use crate::elf::error::ElfError;
+use memmap2::Mmap;
+use std::{fs::File, path::Path};
+
+/// Memory-maps `path` read-only so that it can be handed to
+/// [`ElfFile::parse`](crate::elf::ElfFile::parse) without copying it.
+pub fn map_file<P: AsRef<Path>>(path: P) -> Result<Mmap, ElfError> {
+    let file = File::open(path)?;
+    // SAFETY: the mapping is read-only. Like every mmap-based reader, we rely
+    // on the file not being truncated or rewritten while it is mapped.
+    let map = unsafe { Mmap::map(&file)? };
+    Ok(map)
+}
@@ -0,0 +1,510 @@
+use crate::elf::common::get_flag_names;
+use crate::elf::program_header::PT_NOTE;
+use crate::elf::reader::ElfReader;
+use crate::elf::section_header::SHT_NOTE;
+use crate::elf::*;
+
+use prettytable::{format, row, Table};
+
+/// A run of notes stored in a `SHT_NOTE` section or, for files without
+/// section headers, a `PT_NOTE` segment.
+#[derive(Debug)]
+pub struct ElfNoteTable<'data> {
+    pub index: usize,
+    pub is_segment: bool,
+    pub offset: u64,
+    pub size: u64,
+    pub align: u64,
+    reader: ElfReader<'data>,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ElfNote<'data> {
+    pub name: &'data [u8],
+    pub note_type: ElfWord,
+    pub desc: &'data [u8],
+    reader: ElfReader<'data>,
+}
+
+/// A property from an `NT_GNU_PROPERTY_TYPE_0` note.
+#[derive(Copy, Clone, Debug)]
+pub struct ElfGnuProperty<'data> {
+    pub pr_type: ElfWord,
+    pub data: &'data [u8],
+    reader: ElfReader<'data>,
+}
+
+pub struct ElfNoteIterator<'data> {
+    reader: ElfReader<'data>,
+    offset: usize,
+    end: usize,
+    align: usize,
+}
+
+pub const ELF_NOTE_GNU: &[u8] = b"GNU";
+pub const ELF_NOTE_CORE: &[u8] = b"CORE";
+pub const ELF_NOTE_LINUX: &[u8] = b"LINUX";
+pub const ELF_NOTE_GO: &[u8] = b"Go";
+pub const ELF_NOTE_FREEBSD: &[u8] = b"FreeBSD";
+pub const ELF_NOTE_STAPSDT: &[u8] = b"stapsdt";
+
+pub const NT_GNU_ABI_TAG: u32 = 1;
+pub const NT_GNU_HWCAP: u32 = 2;
+pub const NT_GNU_BUILD_ID: u32 = 3;
+pub const NT_GNU_GOLD_VERSION: u32 = 4;
+pub const NT_GNU_PROPERTY_TYPE_0: u32 = 5;
+
+pub const NT_GO_BUILDID: u32 = 4;
+
+pub const NT_FREEBSD_ABI_TAG: u32 = 1;
+pub const NT_FREEBSD_NOINIT_TAG: u32 = 2;
+pub const NT_FREEBSD_ARCH_TAG: u32 = 3;
+pub const NT_FREEBSD_FEATURE_CTL: u32 = 4;
+
+pub const NT_STAPSDT: u32 = 3;
+
+pub const NT_PRSTATUS: u32 = 1;
+pub const NT_FPREGSET: u32 = 2;
+pub const NT_PRPSINFO: u32 = 3;
+pub const NT_TASKSTRUCT: u32 = 4;
+pub const NT_AUXV: u32 = 6;
+pub const NT_X86_XSTATE: u32 = 0x202;
+pub const NT_ARM_VFP: u32 = 0x400;
+pub const NT_SIGINFO: u32 = 0x53494749;
+pub const NT_FILE: u32 = 0x46494c45;
+pub const NT_PRXFPREG: u32 = 0x46e62b7f;
+
+pub const GNU_ABI_TAG_LINUX: u32 = 0;
+pub const GNU_ABI_TAG_HURD: u32 = 1;
+pub const GNU_ABI_TAG_SOLARIS: u32 = 2;
+pub const GNU_ABI_TAG_FREEBSD: u32 = 3;
+pub const GNU_ABI_TAG_NETBSD: u32 = 4;
+pub const GNU_ABI_TAG_SYLLABLE: u32 = 5;
+pub const GNU_ABI_TAG_NACL: u32 = 6;
+
+pub const GNU_PROPERTY_STACK_SIZE: u32 = 1;
+pub const GNU_PROPERTY_NO_COPY_ON_PROTECTED: u32 = 2;
+pub const GNU_PROPERTY_1_NEEDED: u32 = 0xb0008000;
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_AND: u32 = 0xc0000000;
+pub const GNU_PROPERTY_X86_FEATURE_1_AND: u32 = 0xc0000002;
+pub const GNU_PROPERTY_X86_FEATURE_2_NEEDED: u32 = 0xc0008001;
+pub const GNU_PROPERTY_X86_ISA_1_NEEDED: u32 = 0xc0008002;
+pub const GNU_PROPERTY_X86_FEATURE_2_USED: u32 = 0xc0010001;
+pub const GNU_PROPERTY_X86_ISA_1_USED: u32 = 0xc0010002;
+
+pub const GNU_PROPERTY_1_NEEDED_INDIRECT_EXTERN_ACCESS: u32 = 1 << 0;
+
+pub const GNU_PROPERTY_X86_FEATURE_1_IBT: u32 = 1 << 0;
+pub const GNU_PROPERTY_X86_FEATURE_1_SHSTK: u32 = 1 << 1;
+pub const GNU_PROPERTY_X86_FEATURE_1_LAM_U48: u32 = 1 << 2;
+pub const GNU_PROPERTY_X86_FEATURE_1_LAM_U57: u32 = 1 << 3;
+
+pub const GNU_PROPERTY_X86_ISA_1_BASELINE: u32 = 1 << 0;
+pub const GNU_PROPERTY_X86_ISA_1_V2: u32 = 1 << 1;
+pub const GNU_PROPERTY_X86_ISA_1_V3: u32 = 1 << 2;
+pub const GNU_PROPERTY_X86_ISA_1_V4: u32 = 1 << 3;
+
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_BTI: u32 = 1 << 0;
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_PAC: u32 = 1 << 1;
+pub const GNU_PROPERTY_AARCH64_FEATURE_1_GCS: u32 = 1 << 2;
+
+pub const NT_FREEBSD_FCTL_ASLR_DISABLE: u32 = 1 << 0;
+pub const NT_FREEBSD_FCTL_PROTMAX_DISABLE: u32 = 1 << 1;
+pub const NT_FREEBSD_FCTL_STKGAP_DISABLE: u32 = 1 << 2;
+pub const NT_FREEBSD_FCTL_WXNEEDED: u32 = 1 << 3;
+pub const NT_FREEBSD_FCTL_LA48: u32 = 1 << 4;
+
+const X86_FEATURE_1_NAMES: &[(u32, &str)] = &[
+    (GNU_PROPERTY_X86_FEATURE_1_IBT, "IBT"),
+    (GNU_PROPERTY_X86_FEATURE_1_SHSTK, "SHSTK"),
+    (GNU_PROPERTY_X86_FEATURE_1_LAM_U48, "LAM_U48"),
+    (GNU_PROPERTY_X86_FEATURE_1_LAM_U57, "LAM_U57"),
+];
+
+const X86_ISA_1_NAMES: &[(u32, &str)] = &[
+    (GNU_PROPERTY_X86_ISA_1_BASELINE, "x86-64-baseline"),
+    (GNU_PROPERTY_X86_ISA_1_V2, "x86-64-v2"),
+    (GNU_PROPERTY_X86_ISA_1_V3, "x86-64-v3"),
+    (GNU_PROPERTY_X86_ISA_1_V4, "x86-64-v4"),
+];
+
+const X86_FEATURE_2_NAMES: &[(u32, &str)] = &[
+    (1 << 0, "x86"),
+    (1 << 1, "x87"),
+    (1 << 2, "MMX"),
+    (1 << 3, "XMM"),
+    (1 << 4, "YMM"),
+    (1 << 5, "ZMM"),
+    (1 << 6, "FXSR"),
+    (1 << 7, "XSAVE"),
+    (1 << 8, "XSAVEOPT"),
+    (1 << 9, "XSAVEC"),
+    (1 << 10, "TMM"),
+    (1 << 11, "MASK"),
+];
+
+const AARCH64_FEATURE_1_NAMES: &[(u32, &str)] = &[
+    (GNU_PROPERTY_AARCH64_FEATURE_1_BTI, "BTI"),
+    (GNU_PROPERTY_AARCH64_FEATURE_1_PAC, "PAC"),
+    (GNU_PROPERTY_AARCH64_FEATURE_1_GCS, "GCS"),
+];
+
+const FREEBSD_FEATURE_CTL_NAMES: &[(u32, &str)] = &[
+    (NT_FREEBSD_FCTL_ASLR_DISABLE, "ASLR_DISABLE"),
+    (NT_FREEBSD_FCTL_PROTMAX_DISABLE, "PROTMAX_DISABLE"),
+    (NT_FREEBSD_FCTL_STKGAP_DISABLE, "STKGAP_DISABLE"),
+    (NT_FREEBSD_FCTL_WXNEEDED, "WXNEEDED"),
+    (NT_FREEBSD_FCTL_LA48, "LA48"),
+];
+
+fn align_up(value: usize, align: usize) -> Option<usize> {
+    Some(value.checked_add(align - 1)? & !(align - 1))
+}
+
+impl<'data> ElfNoteTable<'data> {
+    pub fn iter(&self) -> ElfNoteIterator<'data> {
+        ElfNoteIterator {
+            reader: self.reader,
+            offset: self.offset as usize,
+            end: self.offset.saturating_add(self.size) as usize,
+            align: if self.align == 8 { 8 } else { 4 },
+        }
+    }
+}
+
+impl<'data> Iterator for ElfNoteIterator<'data> {
+    type Item = ElfNote<'data>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.end {
+            return None;
+        }
+        let note = self.parse_note();
+        if note.is_none() {
+            self.offset = self.end;
+        }
+        note
+    }
+}
+
+impl<'data> ElfNoteIterator<'data> {
+    /// Decodes the note at `offset` following the binutils layout rules: the
+    /// descriptor and the next note start at the note alignment, measured
+    /// from the start of the current note.
+    fn parse_note(&mut self) -> Option<ElfNote<'data>> {
+        let mut c = self.reader.cursor(self.offset, 12)?;
+        let namesz = c.u32()? as usize;
+        let descsz = c.u32()? as usize;
+        let note_type = c.u32()?;
+
+        let desc_offset = align_up(12usize.checked_add(namesz)?, self.align)?;
+        let next_offset = align_up(desc_offset.checked_add(descsz)?, self.align)?;
+        if self.offset.checked_add(desc_offset.checked_add(descsz)?)? > self.end {
+            return None;
+        }
+        let name = self.reader.bytes(self.offset + 12, namesz)?;
+        let desc = self.reader.bytes(self.offset + desc_offset, descsz)?;
+        self.offset = self.offset.saturating_add(next_offset);
+
+        let name_len = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+        Some(ElfNote {
+            name: &name[..name_len],
+            note_type,
+            desc,
+            reader: self.reader,
+        })
+    }
+}
+
+impl<'data> ElfNote<'data> {
+    pub fn name_str(&self) -> String {
+        String::from_utf8_lossy(self.name).to_string()
+    }
+
+    /// Splits the descriptor of an `NT_GNU_PROPERTY_TYPE_0` note into its
+    /// properties, which are padded to 8 bytes in ELF64 and 4 bytes in ELF32.
+    pub fn gnu_properties(&self) -> Vec<ElfGnuProperty<'data>> {
+        let align = if self.reader.is_32bit() { 4 } else { 8 };
+        let mut properties = Vec::new();
+        let mut c = self.reader.cursor_over(self.desc);
+        while c.remaining() >= 8 {
+            let pr_type = match c.u32() {
+                Some(v) => v,
+                None => break,
+            };
+            let datasz = c.u32().unwrap_or(0) as usize;
+            let data = match c.bytes(datasz) {
+                Some(data) => data,
+                None => break,
+            };
+            properties.push(ElfGnuProperty {
+                pr_type,
+                data,
+                reader: self.reader,
+            });
+            let padding = align_up(datasz, align).unwrap_or(datasz) - datasz;
+            if c.skip(padding.min(c.remaining())).is_none() {
+                break;
+            }
+        }
+        properties
+    }
+
+    pub(crate) fn desc_u32(&self, index: usize) -> Option<u32> {
+        let bytes = self.desc.get(index * 4..index * 4 + 4)?;
+        self.reader.cursor_over(bytes).u32()
+    }
+
+    fn desc_string(&self) -> String {
+        let len = self
+            .desc
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(self.desc.len());
+        String::from_utf8_lossy(&self.desc[..len]).to_string()
+    }
+
+    fn desc_hex(&self) -> String {
+        self.desc.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
+
+impl ElfGnuProperty<'_> {
+    /// Returns the property value for the 4-byte bitmask properties.
+    pub fn value(&self) -> Option<u32> {
+        self.reader.cursor_over(self.data).u32()
+    }
+}
+
+impl<'data> ElfFile<'data> {
+    pub fn note_tables(&self) -> Vec<ElfNoteTable<'data>> {
+        let sections: Vec<ElfNoteTable> = self
+            .section_headers
+            .iter()
+            .enumerate()
+            .filter(|(_, sh)| sh.section_type == SHT_NOTE)
+            .map(|(i, sh)| ElfNoteTable {
+                index: i,
+                is_segment: false,
+                offset: sh.offset,
+                size: sh.size,
+                align: sh.alignment,
+                reader: self.reader(),
+            })
+            .collect();
+        if !sections.is_empty() {
+            return sections;
+        }
+        self.program_headers
+            .iter()
+            .enumerate()
+            .filter(|(_, ph)| ph.segment_type == PT_NOTE)
+            .map(|(i, ph)| ElfNoteTable {
+                index: i,
+                is_segment: true,
+                offset: ph.offset,
+                size: ph.file_size,
+                align: ph.alignment,
+                reader: self.reader(),
+            })
+            .collect()
+    }
+
+    pub fn notes(&self) -> Vec<ElfNote<'data>> {
+        self.note_tables().iter().flat_map(|nt| nt.iter()).collect()
+    }
+
+    pub fn build_id(&self) -> Option<&'data [u8]> {
+        self.notes()
+            .into_iter()
+            .find(|n| n.name == ELF_NOTE_GNU && n.note_type == NT_GNU_BUILD_ID)
+            .map(|n| n.desc)
+    }
+
+    pub fn show_notes(&self) {
+        let note_tables = self.note_tables();
+        if note_tables.is_empty() {
+            println!("There are no notes in this file.");
+            return;
+        }
+        for (i, nt) in note_tables.iter().enumerate() {
+            if i != 0 {
+                println!();
+            }
+            if nt.is_segment {
+                println!(
+                    "Displaying notes found at file offset 0x{:08x} with length 0x{:08x}:",
+                    nt.offset, nt.size
+                );
+            } else {
+                let name = match self.section_headers.get(nt.index) {
+                    Some(sh) => self.get_section_name(&sh),
+                    None => String::new(),
+                };
+                println!("Displaying notes found in: {}", name);
+            }
+
+            let mut table = Table::new();
+            table.set_titles(row!["Owner", "Data size", "Type", "Description"]);
+            for note in nt.iter() {
+                table.add_row(row![
+                    note.name_str(),
+                    format!("0x{:08x}", note.desc.len()),
+                    self.get_note_type_name(&note),
+                    self.get_note_description(&note),
+                ]);
+            }
+            table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+            table.printstd();
+        }
+    }
+
+    fn get_note_type_name(&self, note: &ElfNote) -> String {
+        match (note.name, note.note_type) {
+            (ELF_NOTE_GNU, NT_GNU_ABI_TAG) => "NT_GNU_ABI_TAG (ABI version tag)".to_string(),
+            (ELF_NOTE_GNU, NT_GNU_HWCAP) => {
+                "NT_GNU_HWCAP (DSO-supplied software HWCAP info)".to_string()
+            }
+            (ELF_NOTE_GNU, NT_GNU_BUILD_ID) => {
+                "NT_GNU_BUILD_ID (unique build ID bitstring)".to_string()
+            }
+            (ELF_NOTE_GNU, NT_GNU_GOLD_VERSION) => "NT_GNU_GOLD_VERSION (gold version)".to_string(),
+            (ELF_NOTE_GNU, NT_GNU_PROPERTY_TYPE_0) => "NT_GNU_PROPERTY_TYPE_0".to_string(),
+            (ELF_NOTE_GO, NT_GO_BUILDID) => "GO BUILDID".to_string(),
+            (ELF_NOTE_FREEBSD, NT_FREEBSD_ABI_TAG) => "NT_FREEBSD_ABI_TAG".to_string(),
+            (ELF_NOTE_FREEBSD, NT_FREEBSD_NOINIT_TAG) => "NT_FREEBSD_NOINIT_TAG".to_string(),
+            (ELF_NOTE_FREEBSD, NT_FREEBSD_ARCH_TAG) => "NT_FREEBSD_ARCH_TAG".to_string(),
+            (ELF_NOTE_FREEBSD, NT_FREEBSD_FEATURE_CTL) => "NT_FREEBSD_FEATURE_CTL".to_string(),
+            (ELF_NOTE_STAPSDT, NT_STAPSDT) => {
+                "NT_STAPSDT (SystemTap probe descriptors)".to_string()
+            }
+            (ELF_NOTE_CORE, _) | (ELF_NOTE_LINUX, _) => {
+                self.get_core_note_type_name(note.note_type)
+            }
+            (_, note_type) => format!("Unknown note type: (0x{:08x})", note_type),
+        }
+    }
+
+    fn get_core_note_type_name(&self, note_type: u32) -> String {
+        match note_type {
+            NT_PRSTATUS => "NT_PRSTATUS (prstatus structure)".to_string(),
+            NT_FPREGSET => "NT_FPREGSET (floating point registers)".to_string(),
+            NT_PRPSINFO => "NT_PRPSINFO (prpsinfo structure)".to_string(),
+            NT_TASKSTRUCT => "NT_TASKSTRUCT (task structure)".to_string(),
+            NT_AUXV => "NT_AUXV (auxiliary vector)".to_string(),
+            NT_X86_XSTATE => "NT_X86_XSTATE (x86 XSAVE extended state)".to_string(),
+            NT_ARM_VFP => "NT_ARM_VFP (arm VFP registers)".to_string(),
+            NT_SIGINFO => "NT_SIGINFO (siginfo_t data)".to_string(),
+            NT_FILE => "NT_FILE (mapped files)".to_string(),
+            NT_PRXFPREG => "NT_PRXFPREG (user_xfpregs structure)".to_string(),
+            _ => format!("Unknown note type: (0x{:08x})", note_type),
+        }
+    }
+
+    fn get_note_description(&self, note: &ElfNote) -> String {
+        match (note.name, note.note_type) {
+            (ELF_NOTE_GNU, NT_GNU_ABI_TAG) => self.get_gnu_abi_tag(note),
+            (ELF_NOTE_GNU, NT_GNU_BUILD_ID) => format!("Build ID: {}", note.desc_hex()),
+            (ELF_NOTE_GNU, NT_GNU_GOLD_VERSION) => format!("Version: {}", note.desc_string()),
+            (ELF_NOTE_GNU, NT_GNU_PROPERTY_TYPE_0) => self.get_gnu_properties(note),
+            (ELF_NOTE_GO, NT_GO_BUILDID) => format!("Build ID: {}", note.desc_string()),
+            (ELF_NOTE_FREEBSD, NT_FREEBSD_ABI_TAG) => match note.desc_u32(0) {
+                Some(version) => format!("Version: {}", version),
+                None => "<corrupt>".to_string(),
+            },
+            (ELF_NOTE_FREEBSD, NT_FREEBSD_ARCH_TAG) => format!("Arch: {}", note.desc_string()),
+            (ELF_NOTE_FREEBSD, NT_FREEBSD_FEATURE_CTL) => match note.desc_u32(0) {
+                Some(flags) => format!(
+                    "Features: {}",
+                    get_note_flag_names(flags, FREEBSD_FEATURE_CTL_NAMES)
+                ),
+                None => "<corrupt>".to_string(),
+            },
+            _ => String::new(),
+        }
+    }
+
+    fn get_gnu_abi_tag(&self, note: &ElfNote) -> String {
+        let words: Option<Vec<u32>> = (0..4).map(|i| note.desc_u32(i)).collect();
+        let words = match words {
+            Some(words) => words,
+            None => return "<corrupt GNU_ABI_TAG>".to_string(),
+        };
+        let os = match words[0] {
+            GNU_ABI_TAG_LINUX => "Linux".to_string(),
+            GNU_ABI_TAG_HURD => "Hurd".to_string(),
+            GNU_ABI_TAG_SOLARIS => "Solaris".to_string(),
+            GNU_ABI_TAG_FREEBSD => "FreeBSD".to_string(),
+            GNU_ABI_TAG_NETBSD => "NetBSD".to_string(),
+            GNU_ABI_TAG_SYLLABLE => "Syllable".to_string(),
+            GNU_ABI_TAG_NACL => "NaCl".to_string(),
+            os => format!("Unknown: {}", os),
+        };
+        format!("OS: {}, ABI: {}.{}.{}", os, words[1], words[2], words[3])
+    }
+
+    fn get_gnu_properties(&self, note: &ElfNote) -> String {
+        let properties: Vec<String> = note
+            .gnu_properties()
+            .iter()
+            .map(|p| {
+                let bits = || p.value().unwrap_or(0);
+                match p.pr_type {
+                    GNU_PROPERTY_STACK_SIZE => {
+                        let mut c = p.reader.cursor_over(p.data);
+                        format!("stack size: 0x{:x}", c.addr().unwrap_or(0))
+                    }
+                    GNU_PROPERTY_NO_COPY_ON_PROTECTED => "no copy on protected".to_string(),
+                    GNU_PROPERTY_1_NEEDED => {
+                        let needed = bits();
+                        if needed & GNU_PROPERTY_1_NEEDED_INDIRECT_EXTERN_ACCESS != 0 {
+                            "1_needed: indirect external access".to_string()
+                        } else {
+                            format!("1_needed: 0x{:x}", needed)
+                        }
+                    }
+                    GNU_PROPERTY_X86_FEATURE_1_AND => {
+                        format!(
+                            "x86 feature: {}",
+                            get_note_flag_names(bits(), X86_FEATURE_1_NAMES)
+                        )
+                    }
+                    GNU_PROPERTY_X86_ISA_1_NEEDED => {
+                        format!(
+                            "x86 ISA needed: {}",
+                            get_note_flag_names(bits(), X86_ISA_1_NAMES)
+                        )
+                    }
+                    GNU_PROPERTY_X86_ISA_1_USED => {
+                        format!(
+                            "x86 ISA used: {}",
+                            get_note_flag_names(bits(), X86_ISA_1_NAMES)
+                        )
+                    }
+                    GNU_PROPERTY_X86_FEATURE_2_NEEDED => format!(
+                        "x86 feature needed: {}",
+                        get_note_flag_names(bits(), X86_FEATURE_2_NAMES)
+                    ),
+                    GNU_PROPERTY_X86_FEATURE_2_USED => format!(
+                        "x86 feature used: {}",
+                        get_note_flag_names(bits(), X86_FEATURE_2_NAMES)
+                    ),
+                    GNU_PROPERTY_AARCH64_FEATURE_1_AND => format!(
+                        "AArch64 feature: {}",
+                        get_note_flag_names(bits(), AARCH64_FEATURE_1_NAMES)
+                    ),
+                    pr_type => format!("<unknown type 0x{:x}>", pr_type),
+                }
+            })
+            .collect();
+        format!("Properties: {}", properties.join("\n"))
+    }
+}
+
+/// Note descriptors spell out an empty flag word as `<None>`.
+fn get_note_flag_names(flags: u32, names: &[(u32, &str)]) -> String {
+    if flags == 0 {
+        return "<None>".to_string();
+    }
+    get_flag_names(flags, names, ", ")
+}
@@ -1,9 +1,11 @@
 use crate::elf::common::get_flag_char;
+use crate::elf::reader::ElfReader;
+use crate::elf::section_header::{SHF_ALLOC, SHF_TLS};
+use crate::elf::writer::ElfWriteCursor;
 use crate::elf::*;
 
-use prettytable::{cell, format, row, Table};
+use prettytable::{format, row, Cell, Row, Table};
 
-#[repr(C)]
 #[derive(Copy, Clone, Debug)]
 pub struct ElfProgramHeader {
     pub segment_type: ElfWord,
@@ -16,6 +18,12 @@ pub struct ElfProgramHeader {
     pub alignment: ElfXword,
 }
 
+pub const ELF32_PROGRAM_HEADER_SIZE: usize = 32;
+pub const ELF64_PROGRAM_HEADER_SIZE: usize = 56;
+
+/// `e_phnum` value meaning the real count is in `sh_info` of section 0.
+pub const PN_XNUM: u16 = 0xffff;
+
 pub const PT_NULL: u32 = 0;
 pub const PT_LOAD: u32 = 1;
 pub const PT_DYNAMIC: u32 = 2;
@@ -27,22 +35,163 @@ pub const PT_TLS: u32 = 7;
 pub const PT_GNU_EH_FRAME: u32 = 0x6474e550;
 pub const PT_GNU_STACK: u32 = 0x6474E551;
 pub const PT_GNU_RELRO: u32 = 0x6474E552;
+pub const PT_GNU_PROPERTY: u32 = 0x6474e553;
+pub const PT_GNU_SFRAME: u32 = 0x6474e554;
 
 pub const PF_X: u32 = 1 << 0;
 pub const PF_W: u32 = 1 << 1;
 pub const PF_R: u32 = 1 << 2;
 
-impl ElfFile {
-    pub fn show_program_headers(&self) {
-        let program_headers = &self.program_headers;
+impl ElfProgramHeader {
+    pub(crate) fn entry_size(reader: &ElfReader) -> usize {
+        if reader.is_32bit() {
+            ELF32_PROGRAM_HEADER_SIZE
+        } else {
+            ELF64_PROGRAM_HEADER_SIZE
+        }
+    }
+
+    pub(crate) fn parse(reader: &ElfReader, offset: usize) -> Option<Self> {
+        let mut c = reader.cursor(offset, Self::entry_size(reader))?;
+        if reader.is_32bit() {
+            let segment_type = c.u32()?;
+            let offset = c.addr()?;
+            let virtual_addr = c.addr()?;
+            let physical_addr = c.addr()?;
+            let file_size = c.addr()?;
+            let memory_size = c.addr()?;
+            let flags = c.u32()?;
+            let alignment = c.addr()?;
+            Some(Self {
+                segment_type,
+                flags,
+                offset,
+                virtual_addr,
+                physical_addr,
+                file_size,
+                memory_size,
+                alignment,
+            })
+        } else {
+            Some(Self {
+                segment_type: c.u32()?,
+                flags: c.u32()?,
+                offset: c.addr()?,
+                virtual_addr: c.addr()?,
+                physical_addr: c.addr()?,
+                file_size: c.addr()?,
+                memory_size: c.addr()?,
+                alignment: c.addr()?,
+            })
+        }
+    }
+}
+
+impl ElfProgramHeader {
+    pub(crate) fn write(&self, c: &mut ElfWriteCursor) {
+        c.u32(self.segment_type);
+        if c.is_32bit() {
+            c.addr(self.offset);
+            c.addr(self.virtual_addr);
+            c.addr(self.physical_addr);
+            c.addr(self.file_size);
+            c.addr(self.memory_size);
+            c.u32(self.flags);
+        } else {
+            c.u32(self.flags);
+            c.addr(self.offset);
+            c.addr(self.virtual_addr);
+            c.addr(self.physical_addr);
+            c.addr(self.file_size);
+            c.addr(self.memory_size);
+        }
+        c.addr(self.alignment);
+    }
+
+    /// Returns whether a section belongs to this segment, using the rules of
+    /// GNU readelf's section to segment mapping. A `.tbss` section takes up
+    /// no space in any segment other than `PT_TLS`.
+    pub fn contains_section(&self, sh: &ElfSectionHeader) -> bool {
+        let is_tls = sh.flags & SHF_TLS != 0;
+        let is_alloc = sh.flags & SHF_ALLOC != 0;
+        let tbss_special = is_tls && sh.section_type == SHT_NOBITS && self.segment_type != PT_TLS;
+        if tbss_special {
+            return false;
+        }
+        let size = sh.size;
+
+        let type_matches = if is_tls {
+            matches!(self.segment_type, PT_TLS | PT_GNU_RELRO | PT_LOAD)
+        } else {
+            self.segment_type != PT_TLS && self.segment_type != PT_PHDR
+        };
+        // Loadable segments only hold SHF_ALLOC sections.
+        let alloc_only = matches!(
+            self.segment_type,
+            PT_LOAD | PT_DYNAMIC | PT_GNU_EH_FRAME | PT_GNU_STACK | PT_GNU_RELRO | PT_GNU_SFRAME
+        );
+        if !type_matches || (!is_alloc && alloc_only) {
+            return false;
+        }
+
+        let in_file = sh.section_type == SHT_NOBITS
+            || (sh.offset >= self.offset
+                && sh.offset - self.offset <= self.file_size.wrapping_sub(1)
+                && (sh.offset - self.offset).saturating_add(size) <= self.file_size);
+        let in_memory = !is_alloc
+            || (sh.addr >= self.virtual_addr
+                && sh.addr - self.virtual_addr <= self.memory_size.wrapping_sub(1)
+                && (sh.addr - self.virtual_addr).saturating_add(size) <= self.memory_size);
+        if !in_file || !in_memory {
+            return false;
+        }
 
+        // Empty sections at the edges of PT_DYNAMIC and PT_NOTE are left out.
+        if !matches!(self.segment_type, PT_DYNAMIC | PT_NOTE) || size != 0 || self.memory_size == 0
+        {
+            return true;
+        }
+        let inside_file = sh.section_type == SHT_NOBITS
+            || (sh.offset > self.offset && sh.offset - self.offset < self.file_size);
+        let inside_memory = !is_alloc
+            || (sh.addr > self.virtual_addr && sh.addr - self.virtual_addr < self.memory_size);
+        inside_file && inside_memory
+    }
+}
+
+impl<'data> ElfFile<'data> {
+    /// Returns the program interpreter named by the `PT_INTERP` segment.
+    pub fn interpreter(&self) -> Option<&'data str> {
+        let ph = self
+            .program_headers
+            .iter()
+            .find(|ph| ph.segment_type == PT_INTERP)?;
+        let path = self
+            .reader()
+            .bytes(ph.offset as usize, ph.file_size as usize)?;
+        let len = path.iter().position(|&b| b == 0).unwrap_or(path.len());
+        std::str::from_utf8(&path[..len]).ok()
+    }
+
+    /// Translates a virtual address to a file offset through the `PT_LOAD`
+    /// segments.
+    pub fn virtual_to_offset(&self, addr: u64) -> Option<u64> {
+        self.program_headers
+            .iter()
+            .filter(|ph| ph.segment_type == PT_LOAD)
+            .find(|ph| addr >= ph.virtual_addr && addr - ph.virtual_addr < ph.file_size)
+            .map(|ph| ph.offset + (addr - ph.virtual_addr))
+            .filter(|&offset| offset < self.data.len() as u64)
+    }
+
+    pub fn show_program_headers(&self) {
         println!("Program headers:");
 
         let mut table = Table::new();
         table.set_titles(row![
             "Type", "Offset", "VirtAddr", "PhysAddr", "FileSiz", "MemSiz", "Flags", "Align"
         ]);
-        for ph in program_headers {
+        for ph in self.program_headers.iter() {
             table.add_row(row![
                 self.get_segment_type(ph.segment_type),
                 format!("0x{:X}", ph.offset),
@@ -53,12 +202,55 @@ impl ElfFile {
                 self.get_segment_flags(ph.flags),
                 format!("0x{:X}", ph.alignment),
             ]);
+            if ph.segment_type == PT_INTERP {
+                let interpreter = match self.interpreter() {
+                    Some(path) => format!("[Requesting program interpreter: {}]", path),
+                    None => "[Unable to read program interpreter name]".to_string(),
+                };
+                table.add_row(Row::new(vec![Cell::new(&interpreter).with_hspan(8)]));
+            }
         }
         table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
         table.printstd();
+
+        if self.section_headers.is_empty() {
+            return;
+        }
+        println!();
+        println!("Section to Segment mapping:");
+
+        let mut table = Table::new();
+        table.set_titles(row!["Segment", "Sections"]);
+        for (i, sections) in self.section_to_segment_mapping().iter().enumerate() {
+            let names: Vec<String> = sections
+                .iter()
+                .filter_map(|&index| self.section_headers.get(index))
+                .map(|sh| self.get_section_name(&sh))
+                .collect();
+            table.add_row(row![format!("{:02}", i), names.join(" ")]);
+        }
+        table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+        table.printstd();
+    }
+
+    /// Returns the indexes of the sections in each segment, in program header
+    /// order.
+    pub fn section_to_segment_mapping(&self) -> Vec<Vec<usize>> {
+        self.program_headers
+            .iter()
+            .map(|ph| {
+                self.section_headers
+                    .iter()
+                    .enumerate()
+                    .skip(1)
+                    .filter(|(_, sh)| ph.contains_section(sh))
+                    .map(|(i, _)| i)
+                    .collect()
+            })
+            .collect()
     }
 
-    fn get_segment_type(&self, segment_type: u32) -> String {
+    pub(crate) fn get_segment_type(&self, segment_type: u32) -> String {
         match segment_type {
             PT_NULL => "NULL".to_string(),
             PT_LOAD => "LOAD".to_string(),
@@ -71,11 +263,13 @@ impl ElfFile {
             PT_GNU_EH_FRAME => "GNU_EH_FRAME".to_string(),
             PT_GNU_STACK => "GNU_STACK".to_string(),
             PT_GNU_RELRO => "GNU_RELRO".to_string(),
+            PT_GNU_PROPERTY => "GNU_PROPERTY".to_string(),
+            PT_GNU_SFRAME => "GNU_SFRAME".to_string(),
             _ => format!("<unknown>: {:X}", segment_type),
         }
     }
 
-    fn get_segment_flags(&self, flags: u32) -> String {
+    pub(crate) fn get_segment_flags(&self, flags: u32) -> String {
         let mut s = String::new();
         s.push(get_flag_char(flags, PF_R, 'R'));
         s.push(get_flag_char(flags, PF_W, 'W'));
@@ -0,0 +1,170 @@
+use crate::elf::elf_header::{EI_CLASS, EI_DATA, ELF_CLASS_32, ELF_DATA_2_MSB};
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Endian {
+    Little,
+    Big,
+}
+
+impl Endian {
+    pub fn from_ident(ident: &[u8]) -> Self {
+        match ident.get(EI_DATA) {
+            Some(&ELF_DATA_2_MSB) => Endian::Big,
+            _ => Endian::Little,
+        }
+    }
+}
+
+/// Bounds-checked, endian- and class-aware access to the raw bytes of an ELF file.
+#[derive(Copy, Clone)]
+pub(crate) struct ElfReader<'a> {
+    data: &'a [u8],
+    endian: Endian,
+    is_32bit: bool,
+}
+
+impl<'a> ElfReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            endian: Endian::from_ident(data),
+            is_32bit: data.get(EI_CLASS) == Some(&ELF_CLASS_32),
+        }
+    }
+
+    pub fn is_32bit(&self) -> bool {
+        self.is_32bit
+    }
+
+    pub fn bytes(&self, offset: usize, len: usize) -> Option<&'a [u8]> {
+        self.data.get(offset..offset.checked_add(len)?)
+    }
+
+    /// Returns a cursor positioned at `offset`, or `None` if the `len` bytes
+    /// starting there do not fit in the file.
+    pub fn cursor(&self, offset: usize, len: usize) -> Option<ElfCursor<'a>> {
+        Some(ElfCursor {
+            data: self.bytes(offset, len)?,
+            endian: self.endian,
+            is_32bit: self.is_32bit,
+        })
+    }
+
+    /// Returns a cursor over `data`, a slice previously taken from this file,
+    /// decoding with the file's byte order and class.
+    pub fn cursor_over(&self, data: &'a [u8]) -> ElfCursor<'a> {
+        ElfCursor {
+            data,
+            endian: self.endian,
+            is_32bit: self.is_32bit,
+        }
+    }
+
+    /// Returns whether a table of `num` entries of `entry_size` bytes starting
+    /// at `offset` lies within the file.
+    pub fn contains_table(&self, offset: u64, entry_size: u64, num: u64) -> bool {
+        entry_size
+            .checked_mul(num)
+            .and_then(|size| size.checked_add(offset))
+            .is_some_and(|end| end <= self.data.len() as u64)
+    }
+}
+
+impl fmt::Debug for ElfReader<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ElfReader")
+            .field("len", &self.data.len())
+            .field("endian", &self.endian)
+            .field("is_32bit", &self.is_32bit)
+            .finish()
+    }
+}
+
+/// Sequential field decoder over a bounds-checked slice.
+pub(crate) struct ElfCursor<'a> {
+    data: &'a [u8],
+    endian: Endian,
+    is_32bit: bool,
+}
+
+impl<'a> ElfCursor<'a> {
+    fn take<const N: usize>(&mut self) -> Option<[u8; N]> {
+        if self.data.len() < N {
+            return None;
+        }
+        let (head, rest) = self.data.split_at(N);
+        self.data = rest;
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(head);
+        Some(bytes)
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.data.len() < len {
+            return None;
+        }
+        let (head, rest) = self.data.split_at(len);
+        self.data = rest;
+        Some(head)
+    }
+
+    pub fn skip(&mut self, len: usize) -> Option<()> {
+        self.bytes(len).map(|_| ())
+    }
+
+    pub fn u8(&mut self) -> Option<u8> {
+        self.take::<1>().map(|b| b[0])
+    }
+
+    pub fn u16(&mut self) -> Option<u16> {
+        let bytes = self.take()?;
+        Some(match self.endian {
+            Endian::Little => u16::from_le_bytes(bytes),
+            Endian::Big => u16::from_be_bytes(bytes),
+        })
+    }
+
+    pub fn u32(&mut self) -> Option<u32> {
+        let bytes = self.take()?;
+        Some(match self.endian {
+            Endian::Little => u32::from_le_bytes(bytes),
+            Endian::Big => u32::from_be_bytes(bytes),
+        })
+    }
+
+    pub fn u64(&mut self) -> Option<u64> {
+        let bytes = self.take()?;
+        Some(match self.endian {
+            Endian::Little => u64::from_le_bytes(bytes),
+            Endian::Big => u64::from_be_bytes(bytes),
+        })
+    }
+
+    /// Reads a field that is 4 bytes wide in ELF32 and 8 bytes wide in ELF64
+    /// (addresses, offsets and `Xword` sizes).
+    pub fn addr(&mut self) -> Option<u64> {
+        if self.is_32bit {
+            self.u32().map(u64::from)
+        } else {
+            self.u64()
+        }
+    }
+
+    /// Signed counterpart of [`addr`](Self::addr), for `Sword`/`Sxword` addends.
+    pub fn saddr(&mut self) -> Option<i64> {
+        if self.is_32bit {
+            self.u32().map(|v| v as i32 as i64)
+        } else {
+            self.u64().map(|v| v as i64)
+        }
+    }
+
+    pub fn ident(&mut self) -> Option<[u8; 16]> {
+        self.take()
+    }
+}
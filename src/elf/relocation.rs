@@ -0,0 +1,242 @@
+use crate::elf::elf_symbol::{ElfSymbol, ElfSymbolTable, STT_SECTION};
+use crate::elf::reader::ElfReader;
+use crate::elf::relocation_types::get_relocation_type_name;
+use crate::elf::table::LazyTable;
+use crate::elf::*;
+
+use prettytable::{format, row, Table};
+
+#[derive(Debug)]
+pub struct ElfRelocationTable<'data> {
+    pub index: usize,
+    pub relocations: ElfRelocations<'data>,
+}
+
+#[derive(Debug)]
+pub enum ElfRelocations<'data> {
+    Rel(LazyTable<'data, ElfRel>),
+    Rela(LazyTable<'data, ElfRela>),
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ElfRel {
+    pub offset: ElfAddr,
+    pub info: ElfXword,
+    pub symbol: ElfWord,
+    pub reloc_type: ElfWord,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ElfRela {
+    pub offset: ElfAddr,
+    pub info: ElfXword,
+    pub symbol: ElfWord,
+    pub reloc_type: ElfWord,
+    pub addend: ElfSxword,
+}
+
+pub const ELF32_REL_SIZE: usize = 8;
+pub const ELF64_REL_SIZE: usize = 16;
+pub const ELF32_RELA_SIZE: usize = 12;
+pub const ELF64_RELA_SIZE: usize = 24;
+
+/// Splits `r_info` into its symbol index and relocation type.
+fn split_info(reader: &ElfReader, info: u64) -> (ElfWord, ElfWord) {
+    if reader.is_32bit() {
+        ((info >> 8) as u32, (info & 0xff) as u32)
+    } else {
+        ((info >> 32) as u32, (info & 0xffff_ffff) as u32)
+    }
+}
+
+impl ElfRel {
+    pub(crate) fn entry_size(reader: &ElfReader) -> usize {
+        if reader.is_32bit() {
+            ELF32_REL_SIZE
+        } else {
+            ELF64_REL_SIZE
+        }
+    }
+
+    pub(crate) fn parse(reader: &ElfReader, offset: usize) -> Option<Self> {
+        let mut c = reader.cursor(offset, Self::entry_size(reader))?;
+        let offset = c.addr()?;
+        let info = c.addr()?;
+        let (symbol, reloc_type) = split_info(reader, info);
+        Some(Self {
+            offset,
+            info,
+            symbol,
+            reloc_type,
+        })
+    }
+}
+
+impl ElfRela {
+    pub(crate) fn entry_size(reader: &ElfReader) -> usize {
+        if reader.is_32bit() {
+            ELF32_RELA_SIZE
+        } else {
+            ELF64_RELA_SIZE
+        }
+    }
+
+    pub(crate) fn parse(reader: &ElfReader, offset: usize) -> Option<Self> {
+        let mut c = reader.cursor(offset, Self::entry_size(reader))?;
+        let offset = c.addr()?;
+        let info = c.addr()?;
+        let addend = c.saddr()?;
+        let (symbol, reloc_type) = split_info(reader, info);
+        Some(Self {
+            offset,
+            info,
+            symbol,
+            reloc_type,
+            addend,
+        })
+    }
+}
+
+impl From<ElfRel> for ElfRela {
+    fn from(rel: ElfRel) -> Self {
+        Self {
+            offset: rel.offset,
+            info: rel.info,
+            symbol: rel.symbol,
+            reloc_type: rel.reloc_type,
+            addend: 0,
+        }
+    }
+}
+
+impl<'data> ElfRelocationTable<'data> {
+    pub fn is_rela(&self) -> bool {
+        matches!(self.relocations, ElfRelocations::Rela(_))
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.relocations {
+            ElfRelocations::Rel(table) => table.len(),
+            ElfRelocations::Rela(table) => table.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over the entries, with `SHT_REL` entries given an addend of 0.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = ElfRela> + 'data> {
+        match self.relocations {
+            ElfRelocations::Rel(table) => Box::new(table.iter().map(ElfRela::from)),
+            ElfRelocations::Rela(table) => Box::new(table.iter()),
+        }
+    }
+}
+
+impl ElfFile<'_> {
+    pub fn show_relocations(&self) {
+        self.warn_table_errors(ElfTable::Relocations);
+        if self.relocation_tables.is_empty() {
+            println!("There are no relocations in this file.");
+            return;
+        }
+        for (i, rt) in self.relocation_tables.iter().enumerate() {
+            if i != 0 {
+                println!();
+            }
+            self.show_relocation_table(rt);
+        }
+    }
+
+    fn show_relocation_table(&self, rt: &ElfRelocationTable) {
+        let sh = match self.section_headers.get(rt.index) {
+            Some(sh) => sh,
+            None => return,
+        };
+        println!(
+            "Relocation section '{}' at offset 0x{:x} contains {} entries:",
+            self.get_section_name(&sh),
+            sh.offset,
+            rt.len()
+        );
+
+        let symtab = self.get_symbol_table(sh.link as usize);
+        let mut table = Table::new();
+        if rt.is_rela() {
+            table.set_titles(row![
+                "Offset",
+                "Info",
+                "Type",
+                "Sym. Value",
+                "Sym. Name + Addend"
+            ]);
+        } else {
+            table.set_titles(row!["Offset", "Info", "Type", "Sym. Value", "Sym. Name"]);
+        }
+        for rel in rt.iter() {
+            let symbol = symtab
+                .filter(|_| rel.symbol != 0)
+                .and_then(|st| st.symbols.get(rel.symbol as usize));
+            let value = symbol.map_or(String::new(), |sym| format!("{:x}", sym.value));
+            let mut name = match (symtab, symbol) {
+                (Some(st), Some(sym)) => {
+                    self.get_relocation_symbol_name(st, rel.symbol as usize, &sym)
+                }
+                _ => String::new(),
+            };
+            if rt.is_rela() {
+                name = self.format_addend(name, rel.addend);
+            }
+            table.add_row(row![
+                format!("{:x}", rel.offset),
+                format!("{:x}", rel.info),
+                self.get_relocation_type(rel.reloc_type),
+                value,
+                name,
+            ]);
+        }
+        table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+        table.printstd();
+    }
+
+    fn get_relocation_symbol_name(
+        &self,
+        symtab: &ElfSymbolTable,
+        index: usize,
+        symbol: &ElfSymbol,
+    ) -> String {
+        if symbol.info & 0xf == STT_SECTION && symbol.name == 0 {
+            return match self
+                .section_headers
+                .get(symtab.section_index(index, symbol))
+            {
+                Some(sh) => self.get_section_name(&sh),
+                None => String::new(),
+            };
+        }
+        match self
+            .section_headers
+            .get(symtab.index)
+            .and_then(|symtab| self.get_linked_section(&symtab))
+        {
+            Some(strtab) => self.get_name_from_strtab(&strtab, symbol.name as usize),
+            None => String::new(),
+        }
+    }
+
+    fn format_addend(&self, name: String, addend: i64) -> String {
+        match (name.is_empty(), addend < 0) {
+            (true, _) => format!("{:x}", addend),
+            (false, false) => format!("{} + {:x}", name, addend),
+            (false, true) => format!("{} - {:x}", name, addend.unsigned_abs()),
+        }
+    }
+
+    fn get_relocation_type(&self, reloc_type: u32) -> String {
+        match get_relocation_type_name(self.header.machine_type(), reloc_type) {
+            Some(name) => name.to_string(),
+            None => format!("<unknown>: {:x}", reloc_type),
+        }
+    }
+}
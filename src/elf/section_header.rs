@@ -1,9 +1,10 @@
 use crate::elf::common::get_flag_char;
+use crate::elf::reader::ElfReader;
+use crate::elf::writer::ElfWriteCursor;
 use crate::elf::*;
 
-use prettytable::{cell, format, row, Table};
+use prettytable::{format, row, Table};
 
-#[repr(C)]
 #[derive(Copy, Clone, Debug)]
 pub struct ElfSectionHeader {
     pub name: ElfWord,
@@ -18,6 +19,9 @@ pub struct ElfSectionHeader {
     pub entry_size: ElfXword,
 }
 
+pub const ELF32_SECTION_HEADER_SIZE: usize = 40;
+pub const ELF64_SECTION_HEADER_SIZE: usize = 64;
+
 pub const SHT_NULL: u32 = 0;
 pub const SHT_PROGBITS: u32 = 1;
 pub const SHT_SYMTAB: u32 = 2;
@@ -36,6 +40,7 @@ pub const SHT_PREINIT_ARRAY: u32 = 16;
 pub const SHT_GROUP: u32 = 17;
 pub const SHT_SYMTAB_SHNDX: u32 = 18;
 pub const SHT_GNU_HASH: u32 = 0x6ffffff6;
+pub const SHT_GNU_VERDEF: u32 = 0x6ffffffd;
 pub const SHT_GNU_VERNEED: u32 = 0x6ffffffe;
 pub const SHT_GNU_VERSYM: u32 = 0x6fffffff;
 
@@ -52,12 +57,83 @@ pub const SHF_TLS: u64 = 1 << 10;
 pub const SHF_COMPRESSED: u64 = 1 << 11;
 pub const SHF_EXECLUDE: u64 = 1 << 31;
 
-impl ElfFile {
-    pub fn show_section_headers(&self) {
-        let header = &self.header;
-        let section_headers = &self.section_headers;
-        let shstrtab = &section_headers[header.string_table_index as usize];
+impl ElfSectionHeader {
+    pub(crate) fn entry_size(reader: &ElfReader) -> usize {
+        if reader.is_32bit() {
+            ELF32_SECTION_HEADER_SIZE
+        } else {
+            ELF64_SECTION_HEADER_SIZE
+        }
+    }
+
+    pub(crate) fn parse(reader: &ElfReader, offset: usize) -> Option<Self> {
+        let mut c = reader.cursor(offset, Self::entry_size(reader))?;
+        Some(Self {
+            name: c.u32()?,
+            section_type: c.u32()?,
+            flags: c.addr()?,
+            addr: c.addr()?,
+            offset: c.addr()?,
+            size: c.addr()?,
+            link: c.u32()?,
+            info: c.u32()?,
+            alignment: c.addr()?,
+            entry_size: c.addr()?,
+        })
+    }
+
+    pub(crate) fn write(&self, c: &mut ElfWriteCursor) {
+        c.u32(self.name);
+        c.u32(self.section_type);
+        c.addr(self.flags);
+        c.addr(self.addr);
+        c.addr(self.offset);
+        c.addr(self.size);
+        c.u32(self.link);
+        c.u32(self.info);
+        c.addr(self.alignment);
+        c.addr(self.entry_size);
+    }
 
+    /// Returns the section types `sh_link` may point at, or an empty slice
+    /// when `sh_link` of this section type is not a section index.
+    pub fn expected_link_types(&self) -> &'static [u32] {
+        match self.section_type {
+            SHT_SYMTAB | SHT_DYNSYM | SHT_DYNAMIC | SHT_GNU_VERDEF | SHT_GNU_VERNEED => {
+                &[SHT_STRTAB]
+            }
+            SHT_REL | SHT_RELA | SHT_HASH | SHT_GNU_HASH => &[SHT_SYMTAB, SHT_DYNSYM],
+            SHT_GROUP | SHT_SYMTAB_SHNDX => &[SHT_SYMTAB],
+            SHT_GNU_VERSYM => &[SHT_DYNSYM],
+            _ => &[],
+        }
+    }
+}
+
+impl<'data> ElfFile<'data> {
+    /// Finds a section by index or, when `spec` is not a number, by name.
+    pub fn find_section(&self, spec: &str) -> Option<(usize, ElfSectionHeader)> {
+        if let Ok(index) = spec.parse::<usize>() {
+            return self.section_headers.get(index).map(|sh| (index, sh));
+        }
+        self.section_headers
+            .iter()
+            .enumerate()
+            .find(|(_, sh)| self.get_section_name(sh) == spec)
+    }
+
+    /// Returns the bytes of a section as stored in the file, or `None` for
+    /// `SHT_NOBITS` sections and sections that run past the end of the file.
+    pub fn section_data(&self, sh: &ElfSectionHeader) -> Option<&'data [u8]> {
+        if sh.section_type == SHT_NOBITS {
+            return None;
+        }
+        self.reader().bytes(sh.offset as usize, sh.size as usize)
+    }
+}
+
+impl ElfFile<'_> {
+    pub fn show_section_headers(&self) {
         println!("Section headers:");
 
         let mut table = Table::new();
@@ -65,10 +141,10 @@ impl ElfFile {
             "Nr", "Name", "Type", "Address", "Offset", "Size", "EntSize", "Flags", "Link", "Info",
             "Align"
         ]);
-        for (i, sh) in section_headers.iter().enumerate() {
+        for (i, sh) in self.section_headers.iter().enumerate() {
             table.add_row(row![
                 i,
-                self.get_name_from_strtab(shstrtab, sh.name as usize),
+                self.get_section_name(&sh),
                 self.get_section_type_name(sh.section_type),
                 format!("0x{:x}", sh.addr),
                 format!("0x{:x}", sh.offset),
@@ -82,9 +158,49 @@ impl ElfFile {
         }
         table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
         table.printstd();
+
+        for (i, sh) in self.section_headers.iter().enumerate() {
+            if let Some(warning) = self.get_link_warning(i, &sh) {
+                eprintln!("readelf: Warning: {}", warning);
+            }
+        }
+    }
+
+    /// Describes why `sh_link` of section `index` is invalid, if it points
+    /// at a missing section or one of the wrong type.
+    pub(crate) fn get_link_warning(&self, index: usize, sh: &ElfSectionHeader) -> Option<String> {
+        let expected = sh.expected_link_types();
+        // Relocations that are not against any symbol table use link 0.
+        if expected.is_empty() || (sh.link == 0 && matches!(sh.section_type, SHT_REL | SHT_RELA)) {
+            return None;
+        }
+        let linked_type = self
+            .get_linked_section(sh)
+            .map(|linked| linked.section_type);
+        if linked_type.is_some_and(|t| expected.contains(&t)) {
+            return None;
+        }
+        let expected = expected
+            .iter()
+            .map(|&t| self.get_section_type_name(t))
+            .collect::<Vec<_>>()
+            .join(" or ");
+        Some(match linked_type {
+            Some(t) => format!(
+                "[{}]: Link field ({}) points to a {} section, expected {}",
+                index,
+                sh.link,
+                self.get_section_type_name(t),
+                expected
+            ),
+            None => format!(
+                "[{}]: Link field ({}) should index a {} section",
+                index, sh.link, expected
+            ),
+        })
     }
 
-    fn get_section_type_name(&self, shtype: u32) -> String {
+    pub(crate) fn get_section_type_name(&self, shtype: u32) -> String {
         match shtype {
             SHT_NULL => "NULL".to_string(),
             SHT_PROGBITS => "PROGBITS".to_string(),
@@ -104,13 +220,14 @@ impl ElfFile {
             SHT_GROUP => "GROUP".to_string(),
             SHT_SYMTAB_SHNDX => "SYMTAB SECTION INDICES".to_string(),
             SHT_GNU_HASH => "GNU_HASH".to_string(),
+            SHT_GNU_VERDEF => "VERDEF".to_string(),
             SHT_GNU_VERNEED => "VERNEED".to_string(),
             SHT_GNU_VERSYM => "VERSYM".to_string(),
             _ => format!("{:08X}: <unknown>", shtype),
         }
     }
 
-    fn get_section_flags(&self, flags: u64) -> String {
+    pub(crate) fn get_section_flags(&self, flags: u64) -> String {
         let mut s = String::new();
         s.push(get_flag_char(flags, SHF_WRITE, 'W'));
         s.push(get_flag_char(flags, SHF_ALLOC, 'A'));
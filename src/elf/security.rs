@@ -0,0 +1,298 @@
+use crate::elf::dynamic::{
+    DF_1_NOW, DF_1_PIE, DF_BIND_NOW, DF_TEXTREL, DT_BIND_NOW, DT_FLAGS, DT_FLAGS_1, DT_RPATH,
+    DT_RUNPATH, DT_TEXTREL,
+};
+use crate::elf::elf_header::{ET_DYN, ET_EXEC};
+use crate::elf::elf_symbol::SHN_UNDEF;
+use crate::elf::note::{
+    ELF_NOTE_GNU, GNU_PROPERTY_AARCH64_FEATURE_1_AND, GNU_PROPERTY_AARCH64_FEATURE_1_BTI,
+    GNU_PROPERTY_AARCH64_FEATURE_1_PAC, GNU_PROPERTY_X86_FEATURE_1_AND,
+    GNU_PROPERTY_X86_FEATURE_1_IBT, GNU_PROPERTY_X86_FEATURE_1_SHSTK, NT_GNU_PROPERTY_TYPE_0,
+};
+use crate::elf::program_header::{PF_X, PT_GNU_RELRO, PT_GNU_STACK};
+use crate::elf::*;
+
+use prettytable::{format, row, Table};
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ElfRelro {
+    None,
+    /// `PT_GNU_RELRO` without `BIND_NOW`: the GOT entries of lazily bound
+    /// functions stay writable.
+    Partial,
+    Full,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ElfPie {
+    /// An `ET_EXEC` executable loaded at a fixed address.
+    No,
+    Yes,
+    /// An `ET_DYN` file without `DF_1_PIE`, usually a shared library.
+    SharedObject,
+}
+
+/// The hardening features of an executable or shared object, in the spirit
+/// of checksec.
+#[derive(Clone, Debug)]
+pub struct ElfSecurityReport {
+    pub relro: ElfRelro,
+    /// Whether `PT_GNU_STACK` asks for a non-executable stack.
+    pub nx_stack: bool,
+    pub pie: ElfPie,
+    /// Whether `__stack_chk_fail` or `__stack_chk_guard` is referenced.
+    pub stack_canary: bool,
+    /// The imported `__*_chk` functions of `_FORTIFY_SOURCE`.
+    pub fortified: Vec<String>,
+    pub rpath: Option<String>,
+    pub runpath: Option<String>,
+    pub text_relocations: bool,
+    /// The control-flow protection features marked in the GNU property
+    /// note: IBT and SHSTK on x86, BTI and PAC on AArch64.
+    pub cet: Vec<&'static str>,
+}
+
+impl fmt::Display for ElfRelro {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ElfRelro::None => write!(f, "No RELRO"),
+            ElfRelro::Partial => write!(f, "Partial RELRO"),
+            ElfRelro::Full => write!(f, "Full RELRO"),
+        }
+    }
+}
+
+impl fmt::Display for ElfPie {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ElfPie::No => write!(f, "No PIE"),
+            ElfPie::Yes => write!(f, "PIE enabled"),
+            ElfPie::SharedObject => write!(f, "DSO"),
+        }
+    }
+}
+
+impl ElfSecurityReport {
+    /// Returns the names of the checks that break the hardening policy: no
+    /// full RELRO, an executable stack, a non-PIE executable, an executable
+    /// without a stack canary, RPATH or RUNPATH, and text relocations.
+    /// Shared objects are not required to reference the stack protector,
+    /// since one without stack buffers legitimately has no canary.
+    /// FORTIFY_SOURCE and CET are reported but not enforced.
+    pub fn violations(&self) -> Vec<&'static str> {
+        let mut violations = Vec::new();
+        if self.relro != ElfRelro::Full {
+            violations.push("relro");
+        }
+        if !self.nx_stack {
+            violations.push("nx");
+        }
+        if self.pie == ElfPie::No {
+            violations.push("pie");
+        }
+        if !self.stack_canary && self.pie != ElfPie::SharedObject {
+            violations.push("canary");
+        }
+        if self.rpath.is_some() {
+            violations.push("rpath");
+        }
+        if self.runpath.is_some() {
+            violations.push("runpath");
+        }
+        if self.text_relocations {
+            violations.push("textrel");
+        }
+        violations
+    }
+}
+
+impl ElfFile<'_> {
+    /// Checks the hardening features of an executable or shared object.
+    /// Returns `None` for other file types.
+    pub fn security_report(&self) -> Option<ElfSecurityReport> {
+        if self.header.filetype != ET_EXEC && self.header.filetype != ET_DYN {
+            return None;
+        }
+        let flags = self.get_dynamic_value(DT_FLAGS).unwrap_or(0);
+        let flags_1 = self.get_dynamic_value(DT_FLAGS_1).unwrap_or(0);
+
+        let has_relro = self
+            .program_headers
+            .iter()
+            .any(|ph| ph.segment_type == PT_GNU_RELRO);
+        let bind_now = self.get_dynamic_value(DT_BIND_NOW).is_some()
+            || flags & DF_BIND_NOW != 0
+            || flags_1 & DF_1_NOW != 0;
+        let relro = match (has_relro, bind_now) {
+            (false, _) => ElfRelro::None,
+            (true, false) => ElfRelro::Partial,
+            (true, true) => ElfRelro::Full,
+        };
+
+        let nx_stack = self
+            .program_headers
+            .iter()
+            .find(|ph| ph.segment_type == PT_GNU_STACK)
+            .is_some_and(|ph| ph.flags & PF_X == 0);
+
+        let pie = match (self.header.filetype, flags_1 & DF_1_PIE != 0) {
+            (ET_EXEC, _) => ElfPie::No,
+            (_, true) => ElfPie::Yes,
+            (_, false) => ElfPie::SharedObject,
+        };
+
+        let (stack_canary, fortified) = self.get_hardening_symbols();
+        let dynamic_string = |tag| {
+            self.get_dynamic_value(tag)
+                .and_then(|index| self.get_dynamic_string(index))
+        };
+
+        Some(ElfSecurityReport {
+            relro,
+            nx_stack,
+            pie,
+            stack_canary,
+            fortified,
+            rpath: dynamic_string(DT_RPATH),
+            runpath: dynamic_string(DT_RUNPATH),
+            text_relocations: self.get_dynamic_value(DT_TEXTREL).is_some()
+                || flags & DF_TEXTREL != 0,
+            cet: self.get_cet_features(),
+        })
+    }
+
+    /// Looks for the stack protector and the imported `_FORTIFY_SOURCE`
+    /// functions in all symbol tables.
+    fn get_hardening_symbols(&self) -> (bool, Vec<String>) {
+        let mut stack_canary = false;
+        let mut fortified = Vec::new();
+        for st in &self.symbol_tables {
+            let strtab = match self
+                .section_headers
+                .get(st.index)
+                .and_then(|sh| self.get_linked_section(&sh))
+            {
+                Some(strtab) => strtab,
+                None => continue,
+            };
+            for symbol in st.symbols.iter() {
+                let name = self.get_name_from_strtab(&strtab, symbol.name as usize);
+                if name == "__stack_chk_fail" || name == "__stack_chk_guard" {
+                    stack_canary = true;
+                } else if symbol.section_index == SHN_UNDEF
+                    && name.starts_with("__")
+                    && name.ends_with("_chk")
+                    && !fortified.contains(&name)
+                {
+                    fortified.push(name);
+                }
+            }
+        }
+        (stack_canary, fortified)
+    }
+
+    fn get_cet_features(&self) -> Vec<&'static str> {
+        let mut features = Vec::new();
+        let properties = self
+            .notes()
+            .into_iter()
+            .filter(|n| n.name == ELF_NOTE_GNU && n.note_type == NT_GNU_PROPERTY_TYPE_0)
+            .flat_map(|n| n.gnu_properties());
+        for p in properties {
+            let bits = p.value().unwrap_or(0);
+            let names: &[(u32, &str)] = match p.pr_type {
+                GNU_PROPERTY_X86_FEATURE_1_AND => &[
+                    (GNU_PROPERTY_X86_FEATURE_1_IBT, "IBT"),
+                    (GNU_PROPERTY_X86_FEATURE_1_SHSTK, "SHSTK"),
+                ],
+                GNU_PROPERTY_AARCH64_FEATURE_1_AND => &[
+                    (GNU_PROPERTY_AARCH64_FEATURE_1_BTI, "BTI"),
+                    (GNU_PROPERTY_AARCH64_FEATURE_1_PAC, "PAC"),
+                ],
+                _ => continue,
+            };
+            features.extend(
+                names
+                    .iter()
+                    .filter(|(flag, _)| bits & flag != 0)
+                    .map(|(_, name)| *name),
+            );
+        }
+        features
+    }
+
+    /// Prints the security report and returns its violations.
+    pub fn show_security(&self) -> Vec<&'static str> {
+        let report = match self.security_report() {
+            Some(report) => report,
+            None => {
+                println!("Security checks only apply to executables and shared objects.");
+                return Vec::new();
+            }
+        };
+        println!("Security report:");
+
+        let mut table = Table::new();
+        table.set_titles(row!["Check", "Status"]);
+        table.add_row(row!["RELRO", report.relro]);
+        table.add_row(row![
+            "Stack",
+            if report.nx_stack {
+                "NX enabled"
+            } else {
+                "Executable"
+            }
+        ]);
+        table.add_row(row!["PIE", report.pie]);
+        table.add_row(row![
+            "Canary",
+            if report.stack_canary {
+                "Canary found"
+            } else {
+                "No canary found"
+            }
+        ]);
+        table.add_row(row![
+            "FORTIFY",
+            if report.fortified.is_empty() {
+                "No".to_string()
+            } else {
+                format!("Yes ({})", report.fortified.join(", "))
+            }
+        ]);
+        table.add_row(row!["RPATH", report.rpath.as_deref().unwrap_or("No RPATH")]);
+        table.add_row(row![
+            "RUNPATH",
+            report.runpath.as_deref().unwrap_or("No RUNPATH")
+        ]);
+        table.add_row(row![
+            "TEXTREL",
+            if report.text_relocations {
+                "Text relocations"
+            } else {
+                "No text relocations"
+            }
+        ]);
+        table.add_row(row![
+            "CET",
+            if report.cet.is_empty() {
+                "None".to_string()
+            } else {
+                report.cet.join(", ")
+            }
+        ]);
+        table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+        table.printstd();
+
+        // A single key=value line for scripts.
+        let violations = report.violations();
+        println!();
+        if violations.is_empty() {
+            println!("verdict=pass");
+        } else {
+            println!("verdict=fail violations={}", violations.join(","));
+        }
+        violations
+    }
+}
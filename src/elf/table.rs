@@ -0,0 +1,78 @@
+use crate::elf::reader::ElfReader;
+use std::fmt;
+
+type ParseFn<T> = fn(&ElfReader, usize) -> Option<T>;
+
+/// A view over a table of fixed-size entries that decodes each entry on
+/// access. The table's extent is checked against the file when the view is
+/// created.
+pub struct LazyTable<'data, T> {
+    reader: ElfReader<'data>,
+    offset: usize,
+    entry_size: usize,
+    len: usize,
+    parse: ParseFn<T>,
+}
+
+impl<'data, T> LazyTable<'data, T> {
+    pub(crate) fn new(
+        reader: ElfReader<'data>,
+        offset: usize,
+        entry_size: usize,
+        len: usize,
+        parse: ParseFn<T>,
+    ) -> Self {
+        Self {
+            reader,
+            offset,
+            entry_size,
+            len,
+            parse,
+        }
+    }
+
+    pub(crate) fn empty(reader: ElfReader<'data>, parse: ParseFn<T>) -> Self {
+        Self::new(reader, 0, 0, 0, parse)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<T> {
+        if index >= self.len {
+            return None;
+        }
+        (self.parse)(&self.reader, self.offset + self.entry_size * index)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = T> + 'data
+    where
+        T: 'data,
+    {
+        let table = *self;
+        (0..self.len).filter_map(move |i| table.get(i))
+    }
+}
+
+impl<T> Clone for LazyTable<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for LazyTable<'_, T> {}
+
+impl<T> fmt::Debug for LazyTable<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LazyTable")
+            .field("offset", &self.offset)
+            .field("entry_size", &self.entry_size)
+            .field("len", &self.len)
+            .finish()
+    }
+}
@@ -0,0 +1,474 @@
+use crate::elf::elf_symbol::{ElfSymbolTable, SHN_UNDEF};
+use crate::elf::reader::{ElfCursor, ElfReader};
+use crate::elf::section_header::{SHT_GNU_VERDEF, SHT_GNU_VERNEED, SHT_GNU_VERSYM};
+use crate::elf::table::LazyTable;
+use crate::elf::*;
+
+use prettytable::{format, row, Table};
+use std::collections::HashMap;
+
+/// An entry of a `SHT_GNU_verdef` section. The first name is the version
+/// being defined and the rest are the versions it inherits from.
+#[derive(Clone, Debug)]
+pub struct ElfVerdef {
+    pub offset: usize,
+    pub version: ElfHalf,
+    pub flags: ElfHalf,
+    pub index: ElfHalf,
+    pub count: ElfHalf,
+    pub hash: ElfWord,
+    pub names: Vec<String>,
+    /// The string table offsets of `names`.
+    pub name_offsets: Vec<ElfWord>,
+}
+
+/// An entry of a `SHT_GNU_verneed` section: the versions required from one
+/// shared library.
+#[derive(Clone, Debug)]
+pub struct ElfVerneed {
+    pub offset: usize,
+    pub version: ElfHalf,
+    pub file: String,
+    /// The string table offset of `file`.
+    pub file_offset: ElfWord,
+    pub entries: Vec<ElfVernaux>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ElfVernaux {
+    pub offset: usize,
+    pub hash: ElfWord,
+    pub flags: ElfHalf,
+    pub other: ElfHalf,
+    pub name: String,
+    /// The string table offset of `name`.
+    pub name_offset: ElfWord,
+}
+
+/// The version bound to a symbol through `SHT_GNU_versym`.
+#[derive(Clone, Debug)]
+pub struct ElfSymbolVersion {
+    pub name: String,
+    pub hidden: bool,
+    pub required: bool,
+}
+
+pub const ELF_VERDEF_SIZE: usize = 20;
+pub const ELF_VERDAUX_SIZE: usize = 8;
+pub const ELF_VERNEED_SIZE: usize = 16;
+pub const ELF_VERNAUX_SIZE: usize = 16;
+pub const ELF_VERSYM_SIZE: usize = 2;
+
+pub const VER_NDX_LOCAL: u16 = 0;
+pub const VER_NDX_GLOBAL: u16 = 1;
+pub const VERSYM_HIDDEN: u16 = 0x8000;
+pub const VERSYM_VERSION: u16 = 0x7fff;
+
+pub const VER_FLG_BASE: u16 = 0x1;
+pub const VER_FLG_WEAK: u16 = 0x2;
+pub const VER_FLG_INFO: u16 = 0x4;
+
+fn parse_versym(reader: &ElfReader, offset: usize) -> Option<ElfHalf> {
+    reader.cursor(offset, ELF_VERSYM_SIZE)?.u16()
+}
+
+impl ElfSymbolVersion {
+    /// Returns the suffix appended to the symbol name: `@@VERSION` for the
+    /// default version of a defined symbol, `@VERSION` otherwise.
+    pub fn suffix(&self) -> String {
+        if self.hidden || self.required {
+            format!("@{}", self.name)
+        } else {
+            format!("@@{}", self.name)
+        }
+    }
+}
+
+impl<'data> ElfFile<'data> {
+    /// Returns the `SHT_GNU_versym` table attached to the symbol table in
+    /// section `symtab_index`.
+    pub fn version_symbols(&self, symtab_index: usize) -> Option<LazyTable<'data, ElfHalf>> {
+        let (index, sh) = self.section_headers.iter().enumerate().find(|(_, sh)| {
+            sh.section_type == SHT_GNU_VERSYM && sh.link as usize == symtab_index
+        })?;
+        Self::read_section_table(
+            self.reader(),
+            ElfTable::Versions,
+            index,
+            &sh,
+            ELF_VERSYM_SIZE,
+            parse_versym,
+        )
+        .ok()
+    }
+
+    pub fn version_definitions(&self) -> Vec<ElfVerdef> {
+        self.section_headers
+            .iter()
+            .filter(|sh| sh.section_type == SHT_GNU_VERDEF)
+            .flat_map(|sh| self.read_verdefs(&sh))
+            .collect()
+    }
+
+    pub fn version_requirements(&self) -> Vec<ElfVerneed> {
+        self.section_headers
+            .iter()
+            .filter(|sh| sh.section_type == SHT_GNU_VERNEED)
+            .flat_map(|sh| self.read_verneeds(&sh))
+            .collect()
+    }
+
+    /// Returns the version of symbol `symbol_index` in the symbol table in
+    /// section `symtab_index`, or `None` for unversioned, local and base
+    /// global symbols.
+    pub fn symbol_version(
+        &self,
+        symtab_index: usize,
+        symbol_index: usize,
+    ) -> Option<ElfSymbolVersion> {
+        let versym = self.version_symbols(symtab_index)?.get(symbol_index)?;
+        let st = self.get_symbol_table(symtab_index)?;
+        let defined = st.symbols.get(symbol_index)?.section_index != SHN_UNDEF;
+        self.resolve_version(&self.version_names(), versym, defined)
+    }
+
+    /// Maps version indexes to their names, marking the ones that come from
+    /// `SHT_GNU_verneed`.
+    fn version_names(&self) -> HashMap<ElfHalf, (String, bool)> {
+        let mut names = HashMap::new();
+        for verdef in self.version_definitions() {
+            if let Some(name) = verdef.names.first() {
+                names.insert(verdef.index, (name.clone(), false));
+            }
+        }
+        for verneed in self.version_requirements() {
+            for vernaux in verneed.entries {
+                names.insert(vernaux.other, (vernaux.name, true));
+            }
+        }
+        names
+    }
+
+    fn resolve_version(
+        &self,
+        names: &HashMap<ElfHalf, (String, bool)>,
+        versym: ElfHalf,
+        defined: bool,
+    ) -> Option<ElfSymbolVersion> {
+        let index = versym & VERSYM_VERSION;
+        if index == VER_NDX_LOCAL || index == VER_NDX_GLOBAL {
+            return None;
+        }
+        let (name, required) = names.get(&index)?;
+        Some(ElfSymbolVersion {
+            name: name.clone(),
+            hidden: versym & VERSYM_HIDDEN != 0,
+            required: *required || !defined,
+        })
+    }
+
+    /// Returns the version suffixes for every symbol of `st`, or an empty
+    /// vector when the table is not versioned.
+    pub(crate) fn symbol_version_suffixes(&self, st: &ElfSymbolTable) -> Vec<String> {
+        let versyms = match self.version_symbols(st.index) {
+            Some(versyms) => versyms,
+            None => return Vec::new(),
+        };
+        let names = self.version_names();
+        st.symbols
+            .iter()
+            .zip(versyms.iter())
+            .map(|(symbol, versym)| {
+                self.resolve_version(&names, versym, symbol.section_index != SHN_UNDEF)
+                    .map(|v| v.suffix())
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    fn version_section(&self, sh: &ElfSectionHeader) -> Option<(&'data [u8], ElfSectionHeader)> {
+        let data = self.reader().bytes(sh.offset as usize, sh.size as usize)?;
+        let strtab = self.get_linked_section(sh)?;
+        Some((data, strtab))
+    }
+
+    fn version_cursor(
+        &self,
+        data: &'data [u8],
+        offset: usize,
+        len: usize,
+    ) -> Option<ElfCursor<'data>> {
+        let bytes = data.get(offset..offset.checked_add(len)?)?;
+        Some(self.reader().cursor_over(bytes))
+    }
+
+    /// Walks the `vd_next` chain of a `SHT_GNU_verdef` section, which holds
+    /// `sh_info` entries.
+    pub(crate) fn read_verdefs(&self, sh: &ElfSectionHeader) -> Vec<ElfVerdef> {
+        let mut verdefs = Vec::new();
+        let (data, strtab) = match self.version_section(sh) {
+            Some(section) => section,
+            None => return verdefs,
+        };
+        let mut offset = 0usize;
+        for _ in 0..sh.info {
+            let mut c = match self.version_cursor(data, offset, ELF_VERDEF_SIZE) {
+                Some(c) => c,
+                None => break,
+            };
+            let (version, flags, index, count) = match (c.u16(), c.u16(), c.u16(), c.u16()) {
+                (Some(v), Some(f), Some(i), Some(n)) => (v, f, i, n),
+                _ => break,
+            };
+            let (hash, aux, next) = match (c.u32(), c.u32(), c.u32()) {
+                (Some(h), Some(a), Some(n)) => (h, a, n),
+                _ => break,
+            };
+
+            let mut names = Vec::new();
+            let mut name_offsets = Vec::new();
+            let mut aux_offset = offset.saturating_add(aux as usize);
+            for _ in 0..count {
+                let mut c = match self.version_cursor(data, aux_offset, ELF_VERDAUX_SIZE) {
+                    Some(c) => c,
+                    None => break,
+                };
+                let (name, aux_next) = match (c.u32(), c.u32()) {
+                    (Some(name), Some(next)) => (name, next),
+                    _ => break,
+                };
+                names.push(self.get_name_from_strtab(&strtab, name as usize));
+                name_offsets.push(name);
+                if aux_next == 0 {
+                    break;
+                }
+                aux_offset = aux_offset.saturating_add(aux_next as usize);
+            }
+
+            verdefs.push(ElfVerdef {
+                offset,
+                version,
+                flags,
+                index,
+                count,
+                hash,
+                names,
+                name_offsets,
+            });
+            if next == 0 {
+                break;
+            }
+            offset = offset.saturating_add(next as usize);
+        }
+        verdefs
+    }
+
+    /// Walks the `vn_next` chain of a `SHT_GNU_verneed` section, which holds
+    /// `sh_info` entries.
+    pub(crate) fn read_verneeds(&self, sh: &ElfSectionHeader) -> Vec<ElfVerneed> {
+        let mut verneeds = Vec::new();
+        let (data, strtab) = match self.version_section(sh) {
+            Some(section) => section,
+            None => return verneeds,
+        };
+        let mut offset = 0usize;
+        for _ in 0..sh.info {
+            let mut c = match self.version_cursor(data, offset, ELF_VERNEED_SIZE) {
+                Some(c) => c,
+                None => break,
+            };
+            let (version, count) = match (c.u16(), c.u16()) {
+                (Some(v), Some(n)) => (v, n),
+                _ => break,
+            };
+            let (file, aux, next) = match (c.u32(), c.u32(), c.u32()) {
+                (Some(f), Some(a), Some(n)) => (f, a, n),
+                _ => break,
+            };
+
+            let mut entries = Vec::new();
+            let mut aux_offset = offset.saturating_add(aux as usize);
+            for _ in 0..count {
+                let mut c = match self.version_cursor(data, aux_offset, ELF_VERNAUX_SIZE) {
+                    Some(c) => c,
+                    None => break,
+                };
+                let (hash, flags, other, name, aux_next) =
+                    match (c.u32(), c.u16(), c.u16(), c.u32(), c.u32()) {
+                        (Some(h), Some(f), Some(o), Some(n), Some(next)) => (h, f, o, n, next),
+                        _ => break,
+                    };
+                entries.push(ElfVernaux {
+                    offset: aux_offset,
+                    hash,
+                    flags,
+                    other,
+                    name: self.get_name_from_strtab(&strtab, name as usize),
+                    name_offset: name,
+                });
+                if aux_next == 0 {
+                    break;
+                }
+                aux_offset = aux_offset.saturating_add(aux_next as usize);
+            }
+
+            verneeds.push(ElfVerneed {
+                offset,
+                version,
+                file: self.get_name_from_strtab(&strtab, file as usize),
+                file_offset: file,
+                entries,
+            });
+            if next == 0 {
+                break;
+            }
+            offset = offset.saturating_add(next as usize);
+        }
+        verneeds
+    }
+
+    pub fn show_versions(&self) {
+        let mut found = false;
+        for (i, sh) in self.section_headers.iter().enumerate() {
+            if !matches!(
+                sh.section_type,
+                SHT_GNU_VERSYM | SHT_GNU_VERDEF | SHT_GNU_VERNEED
+            ) {
+                continue;
+            }
+            if found {
+                println!();
+            }
+            found = true;
+            match sh.section_type {
+                SHT_GNU_VERSYM => self.show_version_symbols(i, &sh),
+                SHT_GNU_VERDEF => self.show_version_definitions(&sh),
+                _ => self.show_version_requirements(&sh),
+            }
+        }
+        if !found {
+            println!("No version information found in this file.");
+        }
+    }
+
+    fn show_version_symbols(&self, index: usize, sh: &ElfSectionHeader) {
+        let versyms = Self::read_section_table(
+            self.reader(),
+            ElfTable::Versions,
+            index,
+            sh,
+            ELF_VERSYM_SIZE,
+            parse_versym,
+        )
+        .unwrap_or_else(|_| LazyTable::empty(self.reader(), parse_versym));
+        println!(
+            "Version symbols section '{}' contains {} entries:",
+            self.get_section_name(sh),
+            versyms.len()
+        );
+        let names = self.version_names();
+
+        let mut table = Table::new();
+        table.set_titles(row!["Num", "Version"]);
+        for (i, versym) in versyms.iter().enumerate() {
+            let index = versym & VERSYM_VERSION;
+            let name = match index {
+                VER_NDX_LOCAL => "*local*".to_string(),
+                VER_NDX_GLOBAL => "*global*".to_string(),
+                _ => names
+                    .get(&index)
+                    .map(|(name, _)| name.clone())
+                    .unwrap_or_else(|| "???".to_string()),
+            };
+            let hidden = if versym & VERSYM_HIDDEN != 0 {
+                "h"
+            } else {
+                " "
+            };
+            table.add_row(row![
+                format!("{:03x}", i),
+                format!("{:3x}{}({})", index, hidden, name),
+            ]);
+        }
+        table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+        table.printstd();
+    }
+
+    fn show_version_definitions(&self, sh: &ElfSectionHeader) {
+        let verdefs = self.read_verdefs(sh);
+        println!(
+            "Version definition section '{}' contains {} entries:",
+            self.get_section_name(sh),
+            verdefs.len()
+        );
+
+        let mut table = Table::new();
+        table.set_titles(row![
+            "Offset", "Rev", "Flags", "Index", "Cnt", "Name", "Parents"
+        ]);
+        for verdef in verdefs.iter() {
+            table.add_row(row![
+                format!("0x{:04x}", verdef.offset),
+                format!("{}", verdef.version),
+                self.get_version_flags(verdef.flags),
+                format!("{}", verdef.index),
+                format!("{}", verdef.count),
+                verdef.names.first().cloned().unwrap_or_default(),
+                verdef
+                    .names
+                    .iter()
+                    .skip(1)
+                    .cloned()
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ]);
+        }
+        table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+        table.printstd();
+    }
+
+    fn show_version_requirements(&self, sh: &ElfSectionHeader) {
+        let verneeds = self.read_verneeds(sh);
+        println!(
+            "Version needs section '{}' contains {} entries:",
+            self.get_section_name(sh),
+            verneeds.len()
+        );
+
+        let mut table = Table::new();
+        table.set_titles(row!["Offset", "File", "Name", "Flags", "Version"]);
+        for verneed in verneeds.iter() {
+            for vernaux in verneed.entries.iter() {
+                table.add_row(row![
+                    format!("0x{:04x}", vernaux.offset),
+                    verneed.file,
+                    vernaux.name,
+                    self.get_version_flags(vernaux.flags),
+                    format!("{}", vernaux.other),
+                ]);
+            }
+        }
+        table.set_format(*format::consts::FORMAT_NO_BORDER_LINE_SEPARATOR);
+        table.printstd();
+    }
+
+    fn get_version_flags(&self, flags: ElfHalf) -> String {
+        if flags == 0 {
+            return "none".to_string();
+        }
+        let mut names: Vec<String> = [
+            (VER_FLG_BASE, "BASE"),
+            (VER_FLG_WEAK, "WEAK"),
+            (VER_FLG_INFO, "INFO"),
+        ]
+        .iter()
+        .filter(|(flag, _)| flags & flag != 0)
+        .map(|(_, name)| name.to_string())
+        .collect();
+        let unknown = flags & !(VER_FLG_BASE | VER_FLG_WEAK | VER_FLG_INFO);
+        if unknown != 0 {
+            names.push(format!("<unknown>: {:x}", unknown));
+        }
+        names.join(" | ")
+    }
+}
@@ -0,0 +1,701 @@
+use crate::elf::elf_header::{ElfHeader, ELF32_HEADER_SIZE, ELF64_HEADER_SIZE};
+use crate::elf::elf_symbol::{SHN_LORESERVE, SHN_XINDEX};
+use crate::elf::error::{ElfError, ElfTable};
+use crate::elf::program_header::{
+    ElfProgramHeader, ELF32_PROGRAM_HEADER_SIZE, ELF64_PROGRAM_HEADER_SIZE, PN_XNUM, PT_GNU_RELRO,
+    PT_LOAD, PT_PHDR,
+};
+use crate::elf::reader::Endian;
+use crate::elf::section_header::{
+    ElfSectionHeader, ELF32_SECTION_HEADER_SIZE, ELF64_SECTION_HEADER_SIZE, SHT_NOBITS,
+};
+use crate::elf::*;
+
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// Sequential field encoder, the writing counterpart of `ElfCursor`.
+pub(crate) struct ElfWriteCursor<'a> {
+    data: &'a mut [u8],
+    endian: Endian,
+    is_32bit: bool,
+}
+
+impl ElfWriteCursor<'_> {
+    pub fn is_32bit(&self) -> bool {
+        self.is_32bit
+    }
+
+    fn put<const N: usize>(&mut self, bytes: [u8; N]) {
+        let (head, rest) = std::mem::take(&mut self.data).split_at_mut(N);
+        head.copy_from_slice(&bytes);
+        self.data = rest;
+    }
+
+    pub fn u16(&mut self, value: u16) {
+        match self.endian {
+            Endian::Little => self.put(value.to_le_bytes()),
+            Endian::Big => self.put(value.to_be_bytes()),
+        }
+    }
+
+    pub fn u32(&mut self, value: u32) {
+        match self.endian {
+            Endian::Little => self.put(value.to_le_bytes()),
+            Endian::Big => self.put(value.to_be_bytes()),
+        }
+    }
+
+    pub fn u64(&mut self, value: u64) {
+        match self.endian {
+            Endian::Little => self.put(value.to_le_bytes()),
+            Endian::Big => self.put(value.to_be_bytes()),
+        }
+    }
+
+    /// Writes a field that is 4 bytes wide in ELF32 and 8 bytes wide in
+    /// ELF64.
+    pub fn addr(&mut self, value: u64) {
+        if self.is_32bit {
+            self.u32(value as u32)
+        } else {
+            self.u64(value)
+        }
+    }
+
+    pub fn ident(&mut self, ident: [u8; 16]) {
+        self.put(ident)
+    }
+}
+
+/// A section of an [`ElfWriter`] model.
+#[derive(Clone, Debug)]
+pub struct ElfWriterSection {
+    pub name: String,
+    /// The section header. `sh_name` and `sh_offset` are recomputed when the
+    /// file is written and `sh_size` is taken from `data`, except for
+    /// `SHT_NOBITS` sections. The incoming `sh_offset` only orders the
+    /// sections in the file, so new sections can be appended by giving them
+    /// an offset past the existing ones.
+    pub header: ElfSectionHeader,
+    pub data: Vec<u8>,
+}
+
+/// A segment of an [`ElfWriter`] model.
+#[derive(Clone, Debug)]
+pub struct ElfWriterSegment {
+    /// The program header. `p_offset`, `p_filesz` and `p_memsz` are
+    /// recomputed from the contents of the segment; a segment that holds
+    /// nothing is written as it is.
+    pub header: ElfProgramHeader,
+    /// The indexes of the sections in the segment.
+    pub sections: Vec<usize>,
+    /// Whether the segment holds the program header table.
+    pub program_headers: bool,
+    /// The file contents of a segment that holds no sections, such as the
+    /// segments of a core file.
+    pub data: Vec<u8>,
+}
+
+/// An owned model of an ELF file that can be edited and laid out again.
+#[derive(Clone, Debug)]
+pub struct ElfWriter {
+    /// The ELF header. The class and byte order are taken from `e_ident`.
+    /// Table offsets, sizes and counts are recomputed; the incoming
+    /// `e_phoff` orders the program header table among the sections like
+    /// `sh_offset` does for sections.
+    pub header: ElfHeader,
+    /// The sections, starting with the null section.
+    pub sections: Vec<ElfWriterSection>,
+    pub segments: Vec<ElfWriterSegment>,
+    /// The section header string table, which is rebuilt from the section
+    /// names.
+    pub string_table_index: usize,
+}
+
+/// The file offsets chosen for an [`ElfWriter`] model.
+struct ElfLayout {
+    program_header_offset: u64,
+    section_header_offset: u64,
+    file_size: u64,
+    section_offsets: Vec<u64>,
+    segment_offsets: Vec<Option<u64>>,
+    segments: Vec<ElfProgramHeader>,
+}
+
+/// Something that takes up space in the file, other than the headers.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum ElfLayoutItem {
+    ProgramHeaders,
+    Section(usize),
+    Segment(usize),
+}
+
+impl ElfLayoutItem {
+    /// Returns the table entry reported when the item cannot be placed.
+    fn entry(self) -> (ElfTable, usize) {
+        match self {
+            ElfLayoutItem::ProgramHeaders => (ElfTable::ProgramHeaders, 0),
+            ElfLayoutItem::Section(i) => (ElfTable::SectionData, i),
+            ElfLayoutItem::Segment(i) => (ElfTable::ProgramHeaders, i),
+        }
+    }
+
+    fn overflow(self) -> ElfError {
+        let (table, index) = self.entry();
+        ElfError::LayoutOverflow { table, index }
+    }
+}
+
+fn align_up(value: u64, align: u64) -> Option<u64> {
+    if align <= 1 {
+        return Some(value);
+    }
+    value.checked_next_multiple_of(align)
+}
+
+/// Returns the lowest offset not below `min` that is congruent to `addr`
+/// modulo `align`, as loadable segments require.
+fn congruent_offset(min: u64, addr: u64, align: u64) -> Option<u64> {
+    if align <= 1 {
+        return Some(min);
+    }
+    let offset = (min - min % align).checked_add(addr % align)?;
+    if offset < min {
+        offset.checked_add(align)
+    } else {
+        Some(offset)
+    }
+}
+
+/// Copies `bytes` to `offset` in the output.
+fn copy_data(
+    data: &mut [u8],
+    offset: u64,
+    bytes: &[u8],
+    item: ElfLayoutItem,
+) -> Result<(), ElfError> {
+    let end = offset
+        .checked_add(bytes.len() as u64)
+        .ok_or_else(|| item.overflow())?;
+    data.get_mut(offset as usize..end as usize)
+        .ok_or_else(|| item.overflow())?
+        .copy_from_slice(bytes);
+    Ok(())
+}
+
+impl ElfWriter {
+    fn is_32bit(&self) -> bool {
+        self.header.ident[EI_CLASS] == ELF_CLASS_32
+    }
+
+    fn word_size(&self) -> u64 {
+        if self.is_32bit() {
+            4
+        } else {
+            8
+        }
+    }
+
+    fn header_sizes(&self) -> (u64, u64, u64) {
+        if self.is_32bit() {
+            (
+                ELF32_HEADER_SIZE as u64,
+                ELF32_PROGRAM_HEADER_SIZE as u64,
+                ELF32_SECTION_HEADER_SIZE as u64,
+            )
+        } else {
+            (
+                ELF64_HEADER_SIZE as u64,
+                ELF64_PROGRAM_HEADER_SIZE as u64,
+                ELF64_SECTION_HEADER_SIZE as u64,
+            )
+        }
+    }
+
+    /// Returns the sizes a section takes up in the file and in memory.
+    fn section_sizes(&self, index: usize) -> (u64, u64) {
+        let section = &self.sections[index];
+        if section.header.section_type == SHT_NOBITS {
+            (0, section.header.size)
+        } else {
+            (section.data.len() as u64, section.data.len() as u64)
+        }
+    }
+
+    /// Returns the address of the program header table: the one given by
+    /// `PT_PHDR` or, failing that, the one implied by the loadable segment
+    /// that holds it.
+    fn program_header_addr(&self) -> Option<u64> {
+        if let Some(phdr) = self
+            .segments
+            .iter()
+            .find(|seg| seg.header.segment_type == PT_PHDR)
+        {
+            return Some(phdr.header.virtual_addr);
+        }
+        self.segments
+            .iter()
+            .find(|seg| seg.header.segment_type == PT_LOAD && seg.program_headers)
+            .and_then(|seg| {
+                seg.header.virtual_addr.checked_add(
+                    self.header
+                        .program_header_offset
+                        .saturating_sub(seg.header.offset),
+                )
+            })
+    }
+
+    /// Rebuilds the section header string table from the section names and
+    /// points `sh_name` of every section into it.
+    fn rebuild_string_table(&mut self) {
+        if self.string_table_index == 0 || self.string_table_index >= self.sections.len() {
+            return;
+        }
+        // Names that end another name share its tail, as with `.rela.text`
+        // and `.text`. Sorting by the reversed names puts every name right
+        // after the longer names that end with it.
+        let mut names: Vec<&str> = self.sections.iter().map(|s| s.name.as_str()).collect();
+        names.sort_by(|a, b| b.bytes().rev().cmp(a.bytes().rev()));
+        names.dedup();
+
+        let mut table = vec![0];
+        let mut offsets: HashMap<String, u32> = HashMap::new();
+        let mut previous: Option<(&str, u32)> = None;
+        for name in names {
+            let offset = match previous {
+                _ if name.is_empty() => 0,
+                Some((longer, offset)) if longer.ends_with(name) => {
+                    offset + (longer.len() - name.len()) as u32
+                }
+                _ => {
+                    let offset = table.len() as u32;
+                    table.extend_from_slice(name.as_bytes());
+                    table.push(0);
+                    previous = Some((name, offset));
+                    offset
+                }
+            };
+            offsets.insert(name.to_string(), offset);
+        }
+        for section in &mut self.sections {
+            section.header.name = offsets[&section.name];
+        }
+        self.sections[self.string_table_index].data = table;
+    }
+
+    /// Chooses file offsets for the program header table, the sections,
+    /// the contents of segments without sections and the section header
+    /// table. Data in a loadable segment keeps its place relative to the
+    /// segment address, and the segment is placed at the lowest offset
+    /// congruent to its address; everything else is packed after the data
+    /// before it, honoring its alignment. A loadable segment without file
+    /// contents, such as one holding only `.bss`, keeps its offset and takes
+    /// up no space, and one holding the program headers other than the first
+    /// is placed at the same distance from its address as the first.
+    fn layout(&self) -> Result<ElfLayout, ElfError> {
+        let (header_size, ph_entry_size, sh_entry_size) = self.header_sizes();
+        let phdr_size = ph_entry_size
+            .checked_mul(self.segments.len() as u64)
+            .ok_or_else(|| ElfLayoutItem::ProgramHeaders.overflow())?;
+        let phdr_addr = self.program_header_addr();
+
+        let mut items: Vec<(u64, ElfLayoutItem)> = (1..self.sections.len())
+            .map(|i| (self.sections[i].header.offset, ElfLayoutItem::Section(i)))
+            .collect();
+        if !self.segments.is_empty() {
+            items.push((
+                self.header.program_header_offset,
+                ElfLayoutItem::ProgramHeaders,
+            ));
+        }
+        items.extend(
+            self.segments
+                .iter()
+                .enumerate()
+                .filter(|(_, seg)| !seg.data.is_empty())
+                .map(|(i, seg)| (seg.header.offset, ElfLayoutItem::Segment(i))),
+        );
+        items.sort_by_key(|&(hint, item)| (hint, item));
+
+        let has_file_data: Vec<bool> = self
+            .segments
+            .iter()
+            .map(|seg| {
+                seg.program_headers
+                    || !seg.data.is_empty()
+                    || seg
+                        .sections
+                        .iter()
+                        .any(|&i| i < self.sections.len() && self.section_sizes(i).0 != 0)
+            })
+            .collect();
+        let first_load = self
+            .segments
+            .iter()
+            .position(|seg| seg.header.segment_type == PT_LOAD);
+        let mut load_offsets: Vec<Option<u64>> = vec![None; self.segments.len()];
+        let mut section_offsets = vec![0; self.sections.len()];
+        let mut segment_offsets = vec![None; self.segments.len()];
+        let mut program_header_offset = 0;
+        let mut end = header_size;
+        for (_, item) in items {
+            let (size, align, addr) = match item {
+                ElfLayoutItem::ProgramHeaders => (phdr_size, self.word_size(), phdr_addr),
+                ElfLayoutItem::Section(i) => {
+                    let header = &self.sections[i].header;
+                    (self.section_sizes(i).0, header.alignment, Some(header.addr))
+                }
+                ElfLayoutItem::Segment(i) => {
+                    let seg = &self.segments[i];
+                    let size = seg.data.len() as u64;
+                    (size, seg.header.alignment, Some(seg.header.virtual_addr))
+                }
+            };
+            let load = self.segments.iter().enumerate().position(|(l, seg)| {
+                seg.header.segment_type == PT_LOAD
+                    && match item {
+                        ElfLayoutItem::ProgramHeaders => seg.program_headers,
+                        ElfLayoutItem::Section(i) => seg.sections.contains(&i),
+                        ElfLayoutItem::Segment(i) => l == i,
+                    }
+            });
+            let offset = match (load, addr) {
+                (Some(l), Some(addr)) => {
+                    let seg = &self.segments[l].header;
+                    let delta = addr.saturating_sub(seg.virtual_addr);
+                    let base = match load_offsets[l] {
+                        Some(base) => base,
+                        None => {
+                            let keep = !has_file_data[l]
+                                && congruent_offset(seg.offset, seg.virtual_addr, seg.alignment)
+                                    == Some(seg.offset);
+                            // Kernels before 5.18 derive AT_PHDR from the
+                            // first loadable segment, so a later one holding
+                            // the program headers must keep its difference
+                            // between address and offset.
+                            let first_bias = first_load
+                                .filter(|&first| first != l && self.segments[l].program_headers)
+                                .map(|first| {
+                                    let first_seg = &self.segments[first].header;
+                                    let first_offset =
+                                        load_offsets[first].unwrap_or(first_seg.offset);
+                                    first_seg.virtual_addr.wrapping_sub(first_offset)
+                                });
+                            let base = if keep {
+                                seg.offset
+                            } else if let Some(bias) = first_bias {
+                                seg.virtual_addr
+                                    .checked_sub(bias)
+                                    .ok_or_else(|| item.overflow())?
+                            } else {
+                                congruent_offset(
+                                    end.saturating_sub(delta),
+                                    seg.virtual_addr,
+                                    seg.alignment,
+                                )
+                                .ok_or_else(|| item.overflow())?
+                            };
+                            *load_offsets[l].insert(base)
+                        }
+                    };
+                    let offset = base.checked_add(delta).ok_or_else(|| item.overflow())?;
+                    if offset < end && size != 0 {
+                        let (table, index) = item.entry();
+                        return Err(ElfError::Overlap { table, index });
+                    }
+                    offset
+                }
+                _ => align_up(end, align).ok_or_else(|| item.overflow())?,
+            };
+            // Data without file contents takes up no space.
+            if size != 0 {
+                end = offset.checked_add(size).ok_or_else(|| item.overflow())?;
+            }
+            match item {
+                ElfLayoutItem::ProgramHeaders => program_header_offset = offset,
+                ElfLayoutItem::Section(i) => section_offsets[i] = offset,
+                ElfLayoutItem::Segment(i) => segment_offsets[i] = Some(offset),
+            }
+        }
+
+        let section_table_overflow = ElfError::LayoutOverflow {
+            table: ElfTable::SectionHeaders,
+            index: 0,
+        };
+        let section_header_offset = if self.sections.is_empty() {
+            0
+        } else {
+            align_up(end, self.word_size()).ok_or(section_table_overflow)?
+        };
+        let file_size = sh_entry_size
+            .checked_mul(self.sections.len() as u64)
+            .and_then(|size| size.checked_add(section_header_offset))
+            .filter(|&size| !self.is_32bit() || size <= u32::MAX as u64)
+            .ok_or(ElfError::LayoutOverflow {
+                table: ElfTable::SectionHeaders,
+                index: 0,
+            })?;
+
+        let page_size = self
+            .segments
+            .iter()
+            .filter(|seg| seg.header.segment_type == PT_LOAD)
+            .map(|seg| seg.header.alignment)
+            .max()
+            .unwrap_or(1);
+        let mut segments = Vec::with_capacity(self.segments.len());
+        for (i, seg) in self.segments.iter().enumerate() {
+            // (file offset, file size, address, memory size)
+            let mut members: Vec<(u64, u64, u64, u64)> = seg
+                .sections
+                .iter()
+                .filter(|&&i| i != 0 && i < self.sections.len())
+                .map(|&i| {
+                    let (file_size, memory_size) = self.section_sizes(i);
+                    let addr = self.sections[i].header.addr;
+                    (section_offsets[i], file_size, addr, memory_size)
+                })
+                .collect();
+            if seg.program_headers {
+                let addr = phdr_addr.unwrap_or(seg.header.virtual_addr);
+                members.push((program_header_offset, phdr_size, addr, phdr_size));
+            }
+            if let Some(offset) = segment_offsets[i] {
+                let size = seg.data.len() as u64;
+                let memory_size = seg.header.memory_size;
+                members.push((offset, size, seg.header.virtual_addr, memory_size));
+            }
+            segments.push(
+                Self::layout_segment(seg.header, load_offsets[i], page_size, &members).ok_or(
+                    ElfError::LayoutOverflow {
+                        table: ElfTable::ProgramHeaders,
+                        index: i,
+                    },
+                )?,
+            );
+        }
+
+        Ok(ElfLayout {
+            program_header_offset,
+            section_header_offset,
+            file_size: file_size.max(end),
+            section_offsets,
+            segment_offsets,
+            segments,
+        })
+    }
+
+    /// Recomputes the offset and sizes of a segment from its contents.
+    fn layout_segment(
+        mut ph: ElfProgramHeader,
+        load_offset: Option<u64>,
+        page_size: u64,
+        members: &[(u64, u64, u64, u64)],
+    ) -> Option<ElfProgramHeader> {
+        let first = match members.iter().min_by_key(|m| m.2) {
+            Some(first) => first,
+            None => return Some(ph),
+        };
+        ph.offset = match load_offset {
+            Some(offset) => offset,
+            None => first
+                .0
+                .saturating_sub(first.2.saturating_sub(ph.virtual_addr)),
+        };
+        let mut file_end = None;
+        let mut memory_end = None;
+        for &(offset, file_size, addr, memory_size) in members {
+            if file_size != 0 {
+                file_end = file_end.max(Some(offset.checked_add(file_size)?));
+            }
+            memory_end = memory_end.max(Some(addr.checked_add(memory_size)?));
+        }
+        let file_size = file_end.map_or(0, |end| end.saturating_sub(ph.offset));
+        let memory_size = memory_end.map_or(0, |end| end.saturating_sub(ph.virtual_addr));
+        match ph.segment_type {
+            PT_LOAD => {
+                ph.file_size = file_size;
+                ph.memory_size = memory_size.max(file_size);
+            }
+            PT_GNU_RELRO => {
+                // Linkers round PT_GNU_RELRO up to a page boundary, so keep
+                // the old sizes as long as they only add that tail.
+                let page_end = align_up(ph.virtual_addr.checked_add(memory_size)?, page_size)?
+                    .saturating_sub(ph.virtual_addr);
+                let keep = |old: u64, new: u64| {
+                    if old > new && old <= page_end {
+                        old
+                    } else {
+                        new
+                    }
+                };
+                ph.file_size = keep(ph.file_size, file_size);
+                ph.memory_size = keep(ph.memory_size, memory_size);
+            }
+            _ => {
+                ph.file_size = file_size;
+                ph.memory_size = memory_size;
+            }
+        }
+        Some(ph)
+    }
+
+    /// Lays out the model and returns the bytes of the resulting ELF file.
+    pub fn write(&self) -> Result<Vec<u8>, ElfError> {
+        let mut writer = self.clone();
+        writer.rebuild_string_table();
+        writer.write_layout(&writer.layout()?)
+    }
+
+    pub(crate) fn cursor<'a>(&self, data: &'a mut [u8], offset: u64) -> ElfWriteCursor<'a> {
+        ElfWriteCursor {
+            data: &mut data[offset as usize..],
+            endian: Endian::from_ident(&self.header.ident),
+            is_32bit: self.is_32bit(),
+        }
+    }
+
+    fn write_layout(&self, layout: &ElfLayout) -> Result<Vec<u8>, ElfError> {
+        let (header_size, ph_entry_size, sh_entry_size) = self.header_sizes();
+        let file_size = usize::try_from(layout.file_size)
+            .map_err(|_| ElfError::OutputTooLarge(layout.file_size))?;
+        let mut data = Vec::new();
+        data.try_reserve_exact(file_size)
+            .map_err(|_| ElfError::OutputTooLarge(layout.file_size))?;
+        data.resize(file_size, 0);
+
+        let section_num = self.sections.len();
+        let segment_num = self.segments.len();
+        let mut first_section = self.sections.first().map(|s| s.header);
+        let mut header = self.header;
+        header.elf_header_size = header_size as u16;
+        header.program_header_offset = layout.program_header_offset;
+        header.program_header_size = if segment_num == 0 {
+            0
+        } else {
+            ph_entry_size as u16
+        };
+        header.program_header_num = segment_num as u16;
+        header.section_header_offset = layout.section_header_offset;
+        header.section_header_size = if section_num == 0 {
+            0
+        } else {
+            sh_entry_size as u16
+        };
+        header.section_header_num = section_num as u16;
+        header.string_table_index = self.string_table_index as u16;
+        // Counts that do not fit in the ELF header move to section 0.
+        if let Some(sh) = first_section.as_mut() {
+            if section_num >= SHN_LORESERVE as usize {
+                header.section_header_num = 0;
+                sh.size = section_num as u64;
+            }
+            if self.string_table_index >= SHN_LORESERVE as usize {
+                header.string_table_index = SHN_XINDEX;
+                sh.link = self.string_table_index as u32;
+            }
+            if segment_num >= PN_XNUM as usize {
+                header.program_header_num = PN_XNUM;
+                sh.info = segment_num as u32;
+            }
+        }
+        // The layout places both header tables inside the file.
+        header.write(&mut self.cursor(&mut data, 0));
+        for (i, ph) in layout.segments.iter().enumerate() {
+            let offset = layout.program_header_offset + i as u64 * ph_entry_size;
+            ph.write(&mut self.cursor(&mut data, offset));
+        }
+
+        for (i, (seg, offset)) in self
+            .segments
+            .iter()
+            .zip(&layout.segment_offsets)
+            .enumerate()
+        {
+            if let Some(offset) = offset {
+                copy_data(&mut data, *offset, &seg.data, ElfLayoutItem::Segment(i))?;
+            }
+        }
+        for (i, section) in self.sections.iter().enumerate() {
+            let mut sh = match (i, first_section) {
+                (0, Some(first)) => first,
+                _ => section.header,
+            };
+            if i != 0 {
+                sh.offset = layout.section_offsets[i];
+                sh.size = self.section_sizes(i).1;
+                if sh.section_type != SHT_NOBITS {
+                    copy_data(
+                        &mut data,
+                        sh.offset,
+                        &section.data,
+                        ElfLayoutItem::Section(i),
+                    )?;
+                }
+            }
+            let offset = layout.section_header_offset + i as u64 * sh_entry_size;
+            sh.write(&mut self.cursor(&mut data, offset));
+        }
+        Ok(data)
+    }
+}
+
+impl ElfFile<'_> {
+    /// Copies the file into an [`ElfWriter`] model. Writing the model back
+    /// out without changes gives a file with the same contents, although
+    /// offsets and the section name string table may differ.
+    pub fn to_writer(&self) -> ElfWriter {
+        let sections = self
+            .section_headers
+            .iter()
+            .map(|sh| ElfWriterSection {
+                name: self.get_section_name(&sh),
+                header: sh,
+                data: self.section_data(&sh).unwrap_or_default().to_vec(),
+            })
+            .collect();
+        let phdr_start = self.header.program_header_offset;
+        let phdr_end = phdr_start.saturating_add(
+            self.header.program_header_size as u64 * self.program_headers.len() as u64,
+        );
+        let segments = self
+            .program_headers
+            .iter()
+            .map(|ph| {
+                let sections: Vec<usize> = self
+                    .section_headers
+                    .iter()
+                    .enumerate()
+                    .skip(1)
+                    .filter(|(_, sh)| ph.contains_section(sh))
+                    .map(|(i, _)| i)
+                    .collect();
+                let end = ph.offset.checked_add(ph.file_size);
+                let program_headers = ph.segment_type == PT_PHDR
+                    || (ph.segment_type == PT_LOAD
+                        && ph.offset <= phdr_start
+                        && end.is_some_and(|end| phdr_end <= end));
+                let data = if sections.is_empty() && !program_headers {
+                    end.and_then(|end| self.data.get(ph.offset as usize..end as usize))
+                        .unwrap_or_default()
+                        .to_vec()
+                } else {
+                    Vec::new()
+                };
+                ElfWriterSegment {
+                    header: ph,
+                    sections,
+                    program_headers,
+                    data,
+                }
+            })
+            .collect();
+        ElfWriter {
+            header: self.header,
+            sections,
+            segments,
+            string_table_index: self.string_table_index,
+        }
+    }
+}
@@ -1,19 +1,26 @@
-use readelf::elf::ElfFile;
-use std::env;
+use readelf::elf::{edit::ElfEdit, error::ElfError, map_file, ElfFile};
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::{env, process};
 
 extern crate prettytable;
 extern crate readelf;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    let decompress = take_flag(&mut args, &["-z", "--decompress"], &["hexdump", "strings"]);
+    let ignore_noise = take_flag(&mut args, &["--ignore-noise"], &["diff"]);
     if args.len() < 3 {
-        println!("usage: readelf <command> <file>");
-        std::process::exit(1);
+        usage();
     }
     let command = args.get(1).unwrap();
     let filename = args.get(2).unwrap();
+    let section = || args.get(3).unwrap_or_else(|| usage());
 
-    let elf = ElfFile::read_from_file(filename);
+    let data = map_file(filename).unwrap_or_else(|e| exit_with_error(filename, e));
+    let elf = ElfFile::parse(&data).unwrap_or_else(|e| exit_with_error(filename, e));
 
     match command.as_str() {
         "all" => {
@@ -21,14 +28,156 @@ fn main() {
             println!();
             elf.show_section_headers();
             println!();
+            elf.show_groups();
+            println!();
             elf.show_program_headers();
             println!();
             elf.show_symbol_tables();
+            println!();
+            elf.show_relocations();
+            println!();
+            elf.show_dynamic();
+            println!();
+            elf.show_notes();
+            println!();
+            elf.show_versions();
+            println!();
+            elf.show_hash_histograms();
         }
         "header" => elf.show_header(),
         "sheader" => elf.show_section_headers(),
+        "group" => elf.show_groups(),
         "pheader" => elf.show_program_headers(),
         "symbol" => elf.show_symbol_tables(),
+        "reloc" => elf.show_relocations(),
+        "dynamic" => elf.show_dynamic(),
+        "notes" => elf.show_notes(),
+        "core" => elf.show_core(),
+        "versions" => elf.show_versions(),
+        "histogram" => elf.show_hash_histograms(),
+        "security" => {
+            let violations = elf.show_security();
+            if !violations.is_empty() {
+                std::process::exit(2);
+            }
+        }
+        "hexdump" => elf.show_hex_dump(section(), decompress),
+        "strings" => elf.show_string_dump(section(), decompress),
+        "diff" => {
+            let other_name = section();
+            let other_data =
+                map_file(other_name).unwrap_or_else(|e| exit_with_error(other_name, e));
+            let other =
+                ElfFile::parse(&other_data).unwrap_or_else(|e| exit_with_error(other_name, e));
+            let differences = elf.show_diff(&other, ignore_noise);
+            if !differences.is_empty() {
+                std::process::exit(2);
+            }
+        }
+        "edit" => {
+            let (edits, output) = parse_edits(&args[3..]);
+            let edited = elf
+                .edit(&edits)
+                .unwrap_or_else(|e| exit_with_error(filename, e));
+            let output = output.unwrap_or(filename);
+            replace_file(output, &edited, filename)
+                .unwrap_or_else(|e| exit_with_error(output, e.into()));
+        }
         _ => {}
     };
 }
+
+fn usage() -> ! {
+    println!("usage: readelf <command> <file> [section] [-z]");
+    println!("       readelf diff <file> <file> [--ignore-noise]");
+    println!("       readelf edit <file> <edit>... [--output <file>]");
+    std::process::exit(1);
+}
+
+/// Removes `flags` from the arguments of `commands` and returns whether any
+/// was given. Other commands take their arguments as they are, so that for
+/// example `edit --set-soname -z` sets the soname to `-z`.
+fn take_flag(args: &mut Vec<String>, flags: &[&str], commands: &[&str]) -> bool {
+    if !args
+        .get(1)
+        .is_some_and(|command| commands.contains(&command.as_str()))
+    {
+        return false;
+    }
+    let len = args.len();
+    args.retain(|arg| !flags.contains(&arg.as_str()));
+    args.len() != len
+}
+
+/// Replaces `path` with `data` through a temporary file in the same
+/// directory, so that a failed write leaves the old file intact and a
+/// mapping of it stays valid. The new file gets the permissions of
+/// `original`.
+fn replace_file(path: &str, data: &[u8], original: &str) -> io::Result<()> {
+    let path = Path::new(path);
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "not a file name"))?;
+    let mut temp_name = OsString::from(".");
+    temp_name.push(name);
+    temp_name.push(format!(".{}.tmp", process::id()));
+    let temp = dir.join(temp_name);
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&temp)?;
+    let result = file
+        .write_all(data)
+        .and_then(|()| file.set_permissions(fs::metadata(original)?.permissions()))
+        .and_then(|()| file.sync_all())
+        .and_then(|()| fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Parses the edit options, which apply in the order given, and the output
+/// file. Without `--output` the file is edited in place.
+fn parse_edits(args: &[String]) -> (Vec<ElfEdit>, Option<&String>) {
+    let mut edits = Vec::new();
+    let mut output = None;
+    let mut args = args.iter();
+    while let Some(option) = args.next() {
+        let mut value = || args.next().cloned().unwrap_or_else(|| usage());
+        let edit = match option.as_str() {
+            "--set-interpreter" => ElfEdit::SetInterpreter(value()),
+            "--set-rpath" => ElfEdit::SetRpath(value()),
+            "--remove-rpath" => ElfEdit::RemoveRpath,
+            "--set-runpath" => ElfEdit::SetRunpath(value()),
+            "--remove-runpath" => ElfEdit::RemoveRunpath,
+            "--set-soname" => ElfEdit::SetSoname(value()),
+            "--add-needed" => ElfEdit::AddNeeded(value()),
+            "--remove-needed" => ElfEdit::RemoveNeeded(value()),
+            "--replace-needed" => {
+                let old = value();
+                ElfEdit::ReplaceNeeded(old, value())
+            }
+            "--output" => {
+                output = Some(args.next().unwrap_or_else(|| usage()));
+                continue;
+            }
+            _ => usage(),
+        };
+        edits.push(edit);
+    }
+    if edits.is_empty() {
+        usage();
+    }
+    (edits, output)
+}
+
+fn exit_with_error(filename: &str, e: ElfError) -> ! {
+    eprintln!("readelf: Error: '{}': {}", filename, e);
+    std::process::exit(1);
+}
@@ -0,0 +1,332 @@
+use readelf::elf::dynamic::{DT_STRSZ, DT_STRTAB};
+use readelf::elf::edit::ElfEdit;
+use readelf::elf::error::ElfError;
+use readelf::elf::program_header::{
+    ElfProgramHeader, PF_R, PF_W, PT_DYNAMIC, PT_INTERP, PT_LOAD, PT_PHDR,
+};
+use readelf::elf::section_header::ElfSectionHeader;
+use readelf::elf::ElfFile;
+
+use std::fs;
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+use std::{env, os::unix::fs::PermissionsExt, process};
+
+fn fixture(name: &str) -> Vec<u8> {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    fs::read(&path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+fn edit(data: &[u8], edits: &[ElfEdit]) -> Vec<u8> {
+    ElfFile::parse(data).unwrap().edit(edits).unwrap()
+}
+
+fn section(elf: &ElfFile, name: &str) -> ElfSectionHeader {
+    elf.find_section(name).unwrap().1
+}
+
+fn segment(elf: &ElfFile, segment_type: u32) -> ElfProgramHeader {
+    elf.program_headers
+        .iter()
+        .find(|ph| ph.segment_type == segment_type)
+        .unwrap()
+}
+
+fn loads(elf: &ElfFile) -> Vec<ElfProgramHeader> {
+    elf.program_headers
+        .iter()
+        .filter(|ph| ph.segment_type == PT_LOAD)
+        .collect()
+}
+
+/// Returns the loadable segment whose file contents hold `size` bytes at
+/// `addr`.
+fn load_at(elf: &ElfFile, addr: u64, size: u64) -> ElfProgramHeader {
+    elf.program_headers
+        .iter()
+        .find(|ph| {
+            ph.segment_type == PT_LOAD
+                && ph.virtual_addr <= addr
+                && addr + size <= ph.virtual_addr + ph.file_size
+        })
+        .unwrap_or_else(|| panic!("no PT_LOAD holds {:#x}", addr))
+}
+
+/// Returns the end of the address space used by `elf`, after which the
+/// relocated segments go.
+fn load_end(elf: &ElfFile) -> u64 {
+    loads(elf)
+        .iter()
+        .map(|ph| ph.virtual_addr + ph.memory_size)
+        .max()
+        .unwrap()
+}
+
+/// Checks that `.dynstr` is where `DT_STRTAB` and `DT_STRSZ` say it is.
+fn assert_dynstr_entries(elf: &ElfFile) {
+    let dynstr = section(elf, ".dynstr");
+    assert_eq!(elf.get_dynamic_value(DT_STRTAB), Some(dynstr.addr));
+    assert_eq!(elf.get_dynamic_value(DT_STRSZ), Some(dynstr.size));
+}
+
+/// Checks that `PT_PHDR` still describes the program header table.
+fn assert_program_headers(elf: &ElfFile) {
+    let phdr = segment(elf, PT_PHDR);
+    assert_eq!(phdr.offset, elf.header.program_header_offset);
+    assert_eq!(
+        phdr.file_size,
+        elf.header.program_header_size as u64 * elf.header.program_header_num as u64
+    );
+    let load = load_at(elf, phdr.virtual_addr, phdr.file_size);
+    assert_eq!(
+        phdr.virtual_addr - load.virtual_addr,
+        phdr.offset - load.offset
+    );
+    // Kernels before 5.18 derive AT_PHDR from the first loadable segment.
+    let first = loads(elf)[0];
+    assert_eq!(
+        phdr.virtual_addr.wrapping_sub(phdr.offset),
+        first.virtual_addr.wrapping_sub(first.offset)
+    );
+}
+
+/// Runs an edited copy of the `hello` fixture, which is built for x86-64
+/// Linux, and checks that it still greets.
+#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+fn assert_hello_runs(name: &str, data: &[u8]) {
+    let path = env::temp_dir().join(format!("readelf-{}-{}", name, process::id()));
+    fs::write(&path, data).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    let output = process::Command::new(&path).arg(name).output();
+    let _ = fs::remove_file(&path);
+
+    let output = output.unwrap();
+    assert!(
+        output.status.success(),
+        "{}: {}\n{}",
+        name,
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("hello, {} (2)\n", name)
+    );
+}
+
+#[cfg(not(all(target_os = "linux", target_arch = "x86_64")))]
+fn assert_hello_runs(_name: &str, _data: &[u8]) {}
+
+#[test]
+fn set_runpath_in_place() {
+    let data = fixture("hello");
+    let edited = edit(&data, &[ElfEdit::SetRunpath("/opt/lib".to_string())]);
+    assert_eq!(edited.len(), data.len());
+
+    let original = ElfFile::parse(&data).unwrap();
+    let elf = ElfFile::parse(&edited).unwrap();
+    assert_eq!(elf.runpath().as_deref(), Some("/opt/lib"));
+    assert_eq!(elf.needed_libraries(), original.needed_libraries());
+    assert_eq!(
+        section(&elf, ".dynstr").addr,
+        section(&original, ".dynstr").addr
+    );
+    assert_eq!(loads(&elf).len(), loads(&original).len());
+    assert_dynstr_entries(&elf);
+}
+
+#[test]
+fn set_soname_in_place() {
+    let data = fixture("libhello.so");
+    let edited = edit(&data, &[ElfEdit::SetSoname("libhello.so.2".to_string())]);
+    assert_eq!(edited.len(), data.len());
+
+    let elf = ElfFile::parse(&edited).unwrap();
+    assert_eq!(elf.soname().as_deref(), Some("libhello.so.2"));
+    assert_eq!(elf.rpath().as_deref(), Some("/opt/hello/lib"));
+    assert_dynstr_entries(&elf);
+}
+
+#[test]
+fn replace_needed_in_place() {
+    let data = fixture("libhello.so");
+    let edited = edit(
+        &data,
+        &[ElfEdit::ReplaceNeeded(
+            "libc.so.6".to_string(),
+            "libd.so.6".to_string(),
+        )],
+    );
+    assert_eq!(edited.len(), data.len());
+
+    let elf = ElfFile::parse(&edited).unwrap();
+    assert_eq!(
+        elf.needed_libraries(),
+        vec!["libd.so.6", "ld-linux-x86-64.so.2"]
+    );
+    let files: Vec<String> = elf
+        .version_requirements()
+        .into_iter()
+        .map(|verneed| verneed.file)
+        .collect();
+    assert!(files.contains(&"libd.so.6".to_string()), "{:?}", files);
+    assert!(!files.contains(&"libc.so.6".to_string()), "{:?}", files);
+}
+
+#[test]
+fn replace_needed_shared_with_soname_is_appended() {
+    let data = fixture("libhello.so");
+    let edited = edit(
+        &data,
+        &[
+            ElfEdit::SetSoname("libc.so.6".to_string()),
+            ElfEdit::ReplaceNeeded("libc.so.6".to_string(), "libd.so.6".to_string()),
+        ],
+    );
+
+    let elf = ElfFile::parse(&edited).unwrap();
+    assert_eq!(elf.soname().as_deref(), Some("libc.so.6"));
+    assert_eq!(
+        elf.needed_libraries(),
+        vec!["libd.so.6", "ld-linux-x86-64.so.2"]
+    );
+    assert_dynstr_entries(&elf);
+}
+
+#[test]
+fn grown_dynstr_is_relocated() {
+    let data = fixture("hello");
+    let original = ElfFile::parse(&data).unwrap();
+    let runpath = format!("/opt/{}/lib", "x".repeat(200));
+    let edited = edit(&data, &[ElfEdit::SetRunpath(runpath.clone())]);
+
+    let elf = ElfFile::parse(&edited).unwrap();
+    assert_eq!(elf.runpath(), Some(runpath));
+    assert_eq!(elf.needed_libraries(), original.needed_libraries());
+    assert_eq!(loads(&elf).len(), loads(&original).len() + 1);
+
+    let dynstr = section(&elf, ".dynstr");
+    assert!(dynstr.size > section(&original, ".dynstr").size);
+    let load = load_at(&elf, dynstr.addr, dynstr.size);
+    assert!(load.virtual_addr >= load_end(&original));
+    assert_eq!(load.flags, PF_R);
+    assert_dynstr_entries(&elf);
+
+    // The program header table moves to the start of the new segment.
+    assert_eq!(segment(&elf, PT_PHDR).virtual_addr, load.virtual_addr);
+    assert_program_headers(&elf);
+    assert_hello_runs("dynstr", &edited);
+}
+
+#[test]
+fn grown_dynamic_moves_to_writable_segment() {
+    let data = fixture("hello");
+    let original = ElfFile::parse(&data).unwrap();
+    // Libraries every glibc system has, so that the result still runs.
+    let libraries: Vec<String> = [
+        "libm.so.6",
+        "libdl.so.2",
+        "libpthread.so.0",
+        "librt.so.1",
+        "libutil.so.1",
+        "libresolv.so.2",
+    ]
+    .iter()
+    .map(|name| name.to_string())
+    .collect();
+    let edits: Vec<ElfEdit> = libraries
+        .iter()
+        .map(|name| ElfEdit::AddNeeded(name.clone()))
+        .collect();
+    let edited = edit(&data, &edits);
+
+    let elf = ElfFile::parse(&edited).unwrap();
+    let mut needed = original.needed_libraries();
+    needed.extend(libraries);
+    assert_eq!(elf.needed_libraries(), needed);
+
+    let dynamic = section(&elf, ".dynamic");
+    assert!(dynamic.size > section(&original, ".dynamic").size);
+    assert_eq!(segment(&elf, PT_DYNAMIC).virtual_addr, dynamic.addr);
+    assert_eq!(segment(&elf, PT_DYNAMIC).file_size, dynamic.size);
+    let load = load_at(&elf, dynamic.addr, dynamic.size);
+    assert!(load.virtual_addr >= load_end(&original));
+    assert_eq!(load.flags, PF_R | PF_W);
+
+    assert_eq!(loads(&elf).len(), loads(&original).len() + 2);
+    assert_dynstr_entries(&elf);
+    assert_program_headers(&elf);
+    assert_hello_runs("dynamic", &edited);
+}
+
+#[test]
+fn longer_interpreter_in_place() {
+    let data = fixture("hello");
+    let shorter = edit(&data, &[ElfEdit::SetInterpreter("/lib/ld.so".to_string())]);
+    assert_eq!(shorter.len(), data.len());
+    assert_eq!(
+        ElfFile::parse(&shorter).unwrap().interpreter(),
+        Some("/lib/ld.so")
+    );
+
+    // The section keeps its size, so a longer path still fits.
+    let path = "/lib64/ld-linux-x86-64.so.3";
+    let longer = edit(&shorter, &[ElfEdit::SetInterpreter(path.to_string())]);
+    assert_eq!(longer.len(), data.len());
+
+    let elf = ElfFile::parse(&longer).unwrap();
+    assert_eq!(elf.interpreter(), Some(path));
+    assert_eq!(
+        section(&elf, ".interp").addr,
+        segment(&elf, PT_INTERP).virtual_addr
+    );
+}
+
+#[test]
+fn longer_interpreter_is_relocated() {
+    let data = fixture("hello");
+    let original = ElfFile::parse(&data).unwrap();
+    let path = "/opt/toolchain/lib64/ld-linux-x86-64.so.2";
+    let edited = edit(&data, &[ElfEdit::SetInterpreter(path.to_string())]);
+
+    let elf = ElfFile::parse(&edited).unwrap();
+    assert_eq!(elf.interpreter(), Some(path));
+
+    let interp = section(&elf, ".interp");
+    let segment = segment(&elf, PT_INTERP);
+    assert_eq!(segment.virtual_addr, interp.addr);
+    assert_eq!(segment.file_size, path.len() as u64 + 1);
+    let load = load_at(&elf, interp.addr, interp.size);
+    assert!(load.virtual_addr >= load_end(&original));
+    assert_program_headers(&elf);
+}
+
+#[test]
+fn missing_library_is_an_error() {
+    let data = fixture("hello");
+    let elf = ElfFile::parse(&data).unwrap();
+    for edit in [
+        ElfEdit::RemoveNeeded("libfoo.so".to_string()),
+        ElfEdit::ReplaceNeeded("libfoo.so".to_string(), "libbar.so".to_string()),
+    ] {
+        match elf.edit(&[edit]) {
+            Err(ElfError::NoSuchLibrary(name)) => assert_eq!(name, "libfoo.so"),
+            other => panic!("{:?}", other.map(|d| d.len())),
+        }
+    }
+}
+
+#[test]
+fn missing_interpreter_is_an_error() {
+    let data = fixture("libhello.so");
+    let elf = ElfFile::parse(&data).unwrap();
+    let result = elf.edit(&[ElfEdit::SetInterpreter("/lib/ld.so".to_string())]);
+    assert!(matches!(result, Err(ElfError::NoInterpreter)));
+}
+
+#[test]
+fn static_file_is_not_dynamic() {
+    let data = fixture("hello.o");
+    let elf = ElfFile::parse(&data).unwrap();
+    let result = elf.edit(&[ElfEdit::SetSoname("libhello.so".to_string())]);
+    assert!(matches!(result, Err(ElfError::NotDynamic)));
+}
@@ -0,0 +1,11 @@
+    .text
+    .globl _start
+_start:
+    movl $1, %eax
+    movl $0, %ebx
+    int $0x80
+
+    .bss
+    .globl buffer
+buffer:
+    .zero 65536
@@ -0,0 +1,15 @@
+#include <stdio.h>
+
+int counter = 1;
+__thread int calls;
+
+int greet(const char *name) {
+    calls++;
+    return printf("hello, %s (%d)\n", name, counter + calls);
+}
+
+#ifndef LIBRARY
+int main(int argc, char **argv) {
+    return greet(argc > 1 ? argv[1] : "world") < 0;
+}
+#endif
@@ -0,0 +1,68 @@
+use readelf::elf::error::{ElfError, ElfTable};
+use readelf::elf::ElfFile;
+
+use std::{env, fs, process};
+
+fn fixture(name: &str) -> Vec<u8> {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    fs::read(&path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+/// Returns `hello` with the `sh_entsize` of its `.symtab` set to 3.
+fn broken_symtab() -> Vec<u8> {
+    let mut data = fixture("hello");
+    let elf = ElfFile::parse(&data).unwrap();
+    let (index, _) = elf.find_section(".symtab").unwrap();
+    // sh_entsize is the last field of an ELF64 section header.
+    let offset = elf.header.section_header_offset as usize
+        + index * elf.header.section_header_size as usize
+        + 56;
+    data[offset..offset + 8].copy_from_slice(&3u64.to_le_bytes());
+    data
+}
+
+#[test]
+fn broken_symbol_table_is_skipped() {
+    let data = broken_symtab();
+    let elf = ElfFile::parse(&data).unwrap();
+    let (index, _) = elf.find_section(".symtab").unwrap();
+
+    assert!(elf.symbol_tables.iter().all(|st| st.index != index));
+    assert!(elf
+        .find_section(".dynsym")
+        .is_some_and(|(dynsym, _)| elf.symbol_tables.iter().any(|st| st.index == dynsym)));
+    assert!(matches!(
+        elf.table_errors.as_slice(),
+        [ElfError::BadEntrySize {
+            table: ElfTable::Symbols,
+            size: 3,
+            ..
+        }]
+    ));
+    assert!(!elf.relocation_tables.is_empty());
+    assert!(elf.dynamic_table.is_some());
+}
+
+#[test]
+fn broken_symbol_table_only_warns_when_shown() {
+    let path = env::temp_dir().join(format!("readelf-broken-symtab-{}", process::id()));
+    fs::write(&path, broken_symtab()).unwrap();
+    let run = |command: &str| {
+        process::Command::new(env!("CARGO_BIN_EXE_readelf"))
+            .arg(command)
+            .arg(&path)
+            .output()
+            .unwrap()
+    };
+    let header = run("header");
+    let symbol = run("symbol");
+    let _ = fs::remove_file(&path);
+
+    assert!(header.status.success());
+    assert!(String::from_utf8_lossy(&header.stdout).contains("Entry point address"));
+    assert!(header.stderr.is_empty());
+
+    assert!(symbol.status.success());
+    assert!(String::from_utf8_lossy(&symbol.stdout).contains(".dynsym"));
+    assert!(String::from_utf8_lossy(&symbol.stderr).contains("readelf: Warning:"));
+}
@@ -0,0 +1,259 @@
+use readelf::elf::error::ElfError;
+use readelf::elf::program_header::{PT_DYNAMIC, PT_GNU_RELRO};
+use readelf::elf::section_header::SHT_NOBITS;
+use readelf::elf::ElfFile;
+
+use std::fs;
+
+fn fixture(name: &str) -> Vec<u8> {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    fs::read(&path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+/// Writes `name` back out unchanged and checks that the result describes the
+/// same file. Offsets and the order of the section name string table may
+/// change; everything else must not.
+fn assert_round_trip(name: &str) {
+    let data = fixture(name);
+    let original = ElfFile::parse(&data).unwrap();
+    let written = original.to_writer().write().unwrap();
+    let copy = ElfFile::parse(&written).unwrap();
+
+    let (a, b) = (&original.header, &copy.header);
+    assert_eq!(a.ident, b.ident, "{}: e_ident", name);
+    assert_eq!(a.filetype, b.filetype, "{}: e_type", name);
+    assert_eq!(a.machine, b.machine, "{}: e_machine", name);
+    assert_eq!(a.version, b.version, "{}: e_version", name);
+    assert_eq!(a.entrypoint, b.entrypoint, "{}: e_entry", name);
+    assert_eq!(a.flags, b.flags, "{}: e_flags", name);
+    assert_eq!(
+        a.program_header_num, b.program_header_num,
+        "{}: e_phnum",
+        name
+    );
+    assert_eq!(
+        a.section_header_num, b.section_header_num,
+        "{}: e_shnum",
+        name
+    );
+    assert_eq!(original.string_table_index, copy.string_table_index);
+
+    assert_eq!(original.section_headers.len(), copy.section_headers.len());
+    for (i, (a, b)) in original
+        .section_headers
+        .iter()
+        .zip(copy.section_headers.iter())
+        .enumerate()
+    {
+        let section = original.get_section_name(&a);
+        assert_eq!(
+            section,
+            copy.get_section_name(&b),
+            "{}: section {}",
+            name,
+            i
+        );
+        assert_eq!(a.section_type, b.section_type, "{}: {} type", name, section);
+        assert_eq!(a.flags, b.flags, "{}: {} flags", name, section);
+        assert_eq!(a.addr, b.addr, "{}: {} address", name, section);
+        assert_eq!(a.link, b.link, "{}: {} link", name, section);
+        assert_eq!(a.info, b.info, "{}: {} info", name, section);
+        assert_eq!(a.alignment, b.alignment, "{}: {} alignment", name, section);
+        assert_eq!(a.entry_size, b.entry_size, "{}: {} entsize", name, section);
+        if i != original.string_table_index {
+            assert_eq!(a.size, b.size, "{}: {} size", name, section);
+            if a.section_type != SHT_NOBITS {
+                assert_eq!(
+                    original.section_data(&a),
+                    copy.section_data(&b),
+                    "{}: {} contents",
+                    name,
+                    section
+                );
+            }
+        }
+    }
+
+    assert_eq!(original.program_headers.len(), copy.program_headers.len());
+    for (i, (a, b)) in original
+        .program_headers
+        .iter()
+        .zip(copy.program_headers.iter())
+        .enumerate()
+    {
+        assert_eq!(
+            a.segment_type, b.segment_type,
+            "{}: segment {} type",
+            name, i
+        );
+        assert_eq!(a.flags, b.flags, "{}: segment {} flags", name, i);
+        assert_eq!(
+            a.virtual_addr, b.virtual_addr,
+            "{}: segment {} vaddr",
+            name, i
+        );
+        assert_eq!(
+            a.physical_addr, b.physical_addr,
+            "{}: segment {} paddr",
+            name, i
+        );
+        assert_eq!(a.file_size, b.file_size, "{}: segment {} filesz", name, i);
+        assert_eq!(
+            a.memory_size, b.memory_size,
+            "{}: segment {} memsz",
+            name, i
+        );
+        assert_eq!(a.alignment, b.alignment, "{}: segment {} align", name, i);
+        assert_eq!(
+            a.offset % a.alignment.max(1),
+            b.offset % b.alignment.max(1),
+            "{}: segment {} offset congruence",
+            name,
+            i
+        );
+    }
+
+    assert_eq!(original.symbol_tables.len(), copy.symbol_tables.len());
+    for (a, b) in original.symbol_tables.iter().zip(&copy.symbol_tables) {
+        assert_eq!(a.index, b.index);
+        let strtab_a = original
+            .get_linked_section(&original.section_headers.get(a.index).unwrap())
+            .unwrap();
+        let strtab_b = copy
+            .get_linked_section(&copy.section_headers.get(b.index).unwrap())
+            .unwrap();
+        assert_eq!(a.symbols.len(), b.symbols.len());
+        for (sa, sb) in a.symbols.iter().zip(b.symbols.iter()) {
+            assert_eq!(
+                original.get_name_from_strtab(&strtab_a, sa.name as usize),
+                copy.get_name_from_strtab(&strtab_b, sb.name as usize)
+            );
+            assert_eq!(
+                (sa.info, sa.other, sa.section_index, sa.value, sa.size),
+                (sb.info, sb.other, sb.section_index, sb.value, sb.size)
+            );
+        }
+    }
+
+    let entries = |elf: &ElfFile| -> Vec<(i64, u64)> {
+        elf.dynamic_entries()
+            .iter()
+            .map(|d| (d.tag, d.value))
+            .collect()
+    };
+    assert_eq!(
+        entries(&original),
+        entries(&copy),
+        "{}: dynamic entries",
+        name
+    );
+}
+
+#[test]
+fn round_trip_executable() {
+    assert_round_trip("hello");
+}
+
+#[test]
+fn round_trip_shared_object() {
+    assert_round_trip("libhello.so");
+}
+
+#[test]
+fn round_trip_relocatable() {
+    assert_round_trip("hello.o");
+}
+
+#[test]
+fn round_trip_keeps_bss_only_segment_in_place() {
+    assert_round_trip("bss32");
+
+    let data = fixture("bss32");
+    let written = ElfFile::parse(&data).unwrap().to_writer().write().unwrap();
+    assert_eq!(written.len(), data.len());
+}
+
+#[test]
+fn round_trip_is_stable() {
+    let data = fixture("libhello.so");
+    let once = ElfFile::parse(&data).unwrap().to_writer().write().unwrap();
+    let twice = ElfFile::parse(&once).unwrap().to_writer().write().unwrap();
+    assert_eq!(once, twice);
+}
+
+#[test]
+fn shrunk_section_shrinks_its_segments() {
+    let data = fixture("hello");
+    let elf = ElfFile::parse(&data).unwrap();
+    let (index, dynamic) = elf.find_section(".dynamic").unwrap();
+
+    // Drop the spare DT_NULL slots at the end of `.dynamic`.
+    let entries = elf.dynamic_entries().len() + 1;
+    let size = entries as u64 * dynamic.entry_size;
+    assert!(size < dynamic.size);
+    let mut writer = elf.to_writer();
+    writer.sections[index].data.truncate(size as usize);
+    writer.sections[index].header.size = size;
+    let written = writer.write().unwrap();
+
+    let copy = ElfFile::parse(&written).unwrap();
+    let entries = |elf: &ElfFile| -> Vec<(i64, u64)> {
+        elf.dynamic_entries()
+            .iter()
+            .map(|d| (d.tag, d.value))
+            .collect()
+    };
+    assert_eq!(entries(&copy), entries(&elf));
+    let dynamic = copy.find_section(".dynamic").unwrap().1;
+    assert_eq!(dynamic.size, size);
+    let segment = copy
+        .program_headers
+        .iter()
+        .find(|ph| ph.segment_type == PT_DYNAMIC)
+        .unwrap();
+    assert_eq!(segment.virtual_addr, dynamic.addr);
+    assert_eq!(segment.file_size, size);
+    assert_eq!(segment.memory_size, size);
+
+    // PT_GNU_RELRO keeps its page-rounded end.
+    let relro = |elf: &ElfFile| {
+        elf.program_headers
+            .iter()
+            .find(|ph| ph.segment_type == PT_GNU_RELRO)
+            .map(|ph| ph.virtual_addr + ph.memory_size)
+            .unwrap()
+    };
+    assert_eq!(relro(&copy), relro(&elf));
+}
+
+#[test]
+fn huge_alignment_is_an_error() {
+    let data = fixture("libhello.so");
+    let elf = ElfFile::parse(&data).unwrap();
+    let (index, _) = elf.find_section(".comment").unwrap();
+
+    for alignment in [1 << 40, u64::MAX] {
+        let mut writer = elf.to_writer();
+        writer.sections[index].header.alignment = alignment;
+        match writer.write() {
+            Err(ElfError::OutputTooLarge(_)) | Err(ElfError::LayoutOverflow { .. }) => {}
+            other => panic!("alignment {:#x}: {:?}", alignment, other.map(|d| d.len())),
+        }
+    }
+}
+
+#[test]
+fn address_past_address_space_is_an_error() {
+    let data = fixture("hello");
+    let elf = ElfFile::parse(&data).unwrap();
+    let (index, _) = elf.find_section(".eh_frame").unwrap();
+
+    // `.eh_frame` follows `.rodata` in its segment, so moving its address to
+    // the top of the address space pushes its file offset past u64::MAX.
+    let mut writer = elf.to_writer();
+    writer.sections[index].header.addr = u64::MAX;
+    assert!(matches!(
+        writer.write(),
+        Err(ElfError::LayoutOverflow { .. })
+    ));
+}